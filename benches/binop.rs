@@ -0,0 +1,31 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+use lir::backend::halo2::{bench::bench_inputs, table::generate_binop_table};
+
+fn binop_table(c: &mut Criterion) {
+    let inputs = bench_inputs::<Fr>();
+    let mut group = c.benchmark_group("generate_binop_table");
+    for range in inputs.ranges {
+        group.bench_with_input(BenchmarkId::from_parameter(range), &range, |b, &range| {
+            b.iter(|| generate_binop_table(range, range))
+        });
+    }
+    group.finish();
+}
+
+fn binop_circuit(c: &mut Criterion) {
+    let inputs = bench_inputs::<Fr>();
+    let mut group = c.benchmark_group("binop_circuit");
+    group.sample_size(10);
+    group.bench_with_input(
+        BenchmarkId::new("mock_prover", inputs.k),
+        &inputs,
+        |b, inputs| {
+            b.iter(|| MockProver::run(inputs.k, &inputs.circuit, inputs.instance.clone()).unwrap())
+        },
+    );
+    group.finish();
+}
+
+criterion_group!(benches, binop_table, binop_circuit);
+criterion_main!(benches);
@@ -0,0 +1,37 @@
+use std::ops::RangeInclusive;
+
+use halo2_proofs::halo2curves::FieldExt;
+
+use super::table::BinOpTag;
+use super::utils::{SubCircuit, BLINDING_ROWS};
+use super::BinOpCircuit;
+
+/// Fixed inputs of the benchmarks in `benches/`, so that runs can be
+/// compared with each other.
+#[derive(Clone, Debug)]
+pub struct BenchInputs<F: FieldExt> {
+    /// Operand widths to generate the binop table at.
+    pub ranges: RangeInclusive<u32>,
+    /// A few operations, proved against the table `BinOpCircuit` loads.
+    pub circuit: BinOpCircuit<F>,
+    /// Public inputs of `circuit`.
+    pub instance: Vec<Vec<F>>,
+    /// Smallest `k` that fits `circuit`.
+    pub k: u32,
+}
+
+pub fn bench_inputs<F: FieldExt>() -> BenchInputs<F> {
+    let circuit = BinOpCircuit::from_ops(vec![
+        (BinOpTag::ADD as u64, 3, 5, 8),
+        (BinOpTag::MUL as u64, 3, 5, 15),
+        (BinOpTag::ADD as u64, 7, 7, 14),
+        (BinOpTag::MUL as u64, 2, 6, 12),
+    ]);
+    let rows = circuit.min_num_rows() + BLINDING_ROWS;
+    BenchInputs {
+        ranges: 2..=8,
+        instance: circuit.instance(),
+        k: rows.next_power_of_two().trailing_zeros(),
+        circuit,
+    }
+}
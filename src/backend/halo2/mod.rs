@@ -4,14 +4,16 @@ use halo2_proofs::{
     plonk::{Circuit, ConstraintSystem, Error},
 };
 use std::marker::PhantomData;
+pub mod bench;
 mod convert;
-mod table;
+pub mod table;
 mod utils;
 mod vm_circuit;
 mod witness;
 
 use table::*;
 use utils::SubCircuit;
+pub use vm_circuit::{min_k_for_binop, min_k_for_binop_ops, BinOpCircuit, ExecutionTrace};
 
 use crate::ExecTrace;
 
@@ -26,7 +28,7 @@ pub struct LookupConfig<F: FieldExt> {
 
 impl<F: FieldExt> LookupConfig<F> {
     fn new(meta: &mut ConstraintSystem<F>) -> Self {
-        let binop_table = table::BinaryOperationTable::construct(meta);
+        let binop_table = table::BinaryOperationTable::construct(meta, 4);
         let unaryop_table = table::UnaryOperationTable::construct(meta);
         let blockexit_table = table::BlockExitTable::construct(meta);
         let call_table = table::CallTable::construct(meta);
@@ -41,20 +43,6 @@ impl<F: FieldExt> LookupConfig<F> {
     }
 }
 
-fn generate_binop_table() -> Vec<[u8; 4]> {
-    let mut all_cases = vec![];
-    // TODO: for all cases
-    all_cases.push([1, 1, 1, 2]);
-    all_cases
-}
-
-fn generate_unop_table() -> Vec<[u8; 3]> {
-    let mut all_cases = vec![];
-    // TODO: for all cases
-    all_cases.push([1, 1, 1]);
-    all_cases
-}
-
 #[derive(Clone, Debug)]
 pub struct LookupCircuit<F: FieldExt> {
     _marker: PhantomData<F>,
@@ -87,12 +75,12 @@ impl<F: FieldExt> Circuit<F> for LookupCircuit<F> {
         config: Self::Config,
         mut layouter: impl Layouter<F>,
     ) -> Result<(), Error> {
-        let precomputed_binop = generate_binop_table();
-        let precomputed_unop = generate_unop_table();
+        let precomputed_binop = generate_binop_table(4, 4);
+        let precomputed_unop = generate_unaryop_table(4);
         config.binop_table.load(&mut layouter, precomputed_binop)?;
         config.unaryop_table.load(&mut layouter, precomputed_unop)?;
-        config.blockexit_table.load(&mut layouter)?;
-        config.call_table.load(&mut layouter)?;
+        config.blockexit_table.load(&mut layouter, vec![])?;
+        config.call_table.load(&mut layouter, vec![])?;
         Ok(())
     }
 }
@@ -0,0 +1,57 @@
+use halo2_proofs::plonk;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum TableError {
+    #[error("{value} is not a valid tag of the {table} table")]
+    InvalidTag { table: &'static str, value: u64 },
+    #[error("row has {width} values, but the {table} table has {columns} columns")]
+    RowTooWide {
+        table: &'static str,
+        width: usize,
+        columns: usize,
+    },
+    #[error("{rows} rows do not fit in the {table} table, which has {capacity}")]
+    TooManyRows {
+        table: &'static str,
+        rows: usize,
+        capacity: usize,
+    },
+    #[error("offset {offset} is past the {capacity} rows of the region")]
+    OffsetOutOfRange { offset: usize, capacity: usize },
+    #[error("table has {columns} columns but {annotations} annotations")]
+    ShapeMismatch { columns: usize, annotations: usize },
+    #[error("op {tag} is not among the ops allowed by the config")]
+    OpNotAllowed { tag: u64 },
+    #[error("{mnemonic:?} is not a mnemonic of the {table} table")]
+    UnknownMnemonic {
+        table: &'static str,
+        mnemonic: String,
+    },
+    #[error("table would have {estimated_rows} rows, more than allowed")]
+    TableTooLarge { estimated_rows: u128 },
+    #[error("row {row} does not hold the result of its op")]
+    InconsistentRow { row: usize },
+    #[error("{value} does not fit in {bits} bits")]
+    ValueTooWide { value: u128, bits: u32 },
+    #[error(transparent)]
+    Halo2(#[from] plonk::Error),
+    #[cfg(feature = "serde")]
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[cfg(feature = "serde")]
+    #[error(transparent)]
+    Bincode(#[from] bincode::Error),
+}
+
+/// Lets table loading be called with `?` inside `synthesize`. halo2 has no
+/// variant to carry our context, so anything but a wrapped halo2 error
+/// becomes `Error::Synthesis`.
+impl From<TableError> for plonk::Error {
+    fn from(error: TableError) -> Self {
+        match error {
+            TableError::Halo2(error) => error,
+            _ => plonk::Error::Synthesis,
+        }
+    }
+}
@@ -0,0 +1,2888 @@
+use std::collections::{HashMap, HashSet};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, TableColumn, *},
+    poly::Rotation,
+};
+
+use strum::IntoEnumIterator;
+
+use crate::backend::halo2::utils::{log_debug, log_trace, Expr};
+use crate::impl_expr;
+
+mod error;
+mod precomputed;
+use crate::ops;
+pub use error::TableError;
+#[cfg(test)]
+use ops::generate_binop_table_serial;
+pub use ops::{
+    canonical_binop_table, estimated_binop_rows, generate_arith_table, generate_binop_table,
+    generate_binop_table_for, generate_comparison_table, generate_unaryop_table,
+    generate_wide_binop_table_for, mask, max_value, merge_binop_tables, BinOpTag, BlockExitTag,
+    UnaryOpTag,
+};
+#[cfg(feature = "digest")]
+pub use precomputed::table_digest;
+pub use precomputed::PrecomputedBinopTable;
+
+/// Most rows a precomputed table assigns in one region. Larger tables are
+/// split into consecutive regions of this many rows, which the floor
+/// planner stacks in the same columns.
+pub const MAX_REGION_ROWS: usize = 1 << 12;
+
+/// Rejects the first of `tags` that does not convert to a `T`.
+fn check_tags<T: TryFrom<u8, Error = TableError>>(
+    table: &'static str,
+    tags: impl IntoIterator<Item = u64>,
+) -> Result<(), TableError> {
+    for value in tags {
+        let value = u8::try_from(value).map_err(|_| TableError::InvalidTag { table, value })?;
+        T::try_from(value)?;
+    }
+    Ok(())
+}
+
+/// Implements `TryFrom<u8>` for a tag enum by matching variant discriminants,
+/// and `TryFrom<u64>` for the tags of table rows, which rejects a value
+/// that is not a byte rather than truncating it.
+macro_rules! impl_try_from_int {
+    ($type:ty, $table:literal) => {
+        impl TryFrom<u8> for $type {
+            type Error = TableError;
+
+            fn try_from(value: u8) -> Result<Self, Self::Error> {
+                Self::iter()
+                    .find(|tag| *tag as u8 == value)
+                    .ok_or(TableError::InvalidTag {
+                        table: $table,
+                        value: value as u64,
+                    })
+            }
+        }
+
+        impl TryFrom<u64> for $type {
+            type Error = TableError;
+
+            fn try_from(value: u64) -> Result<Self, Self::Error> {
+                let tag = u8::try_from(value).map_err(|_| TableError::InvalidTag {
+                    table: $table,
+                    value,
+                })?;
+                Self::try_from(tag)
+            }
+        }
+    };
+}
+
+/// Parses a tag from its mnemonic, e.g. `"add"` for `BinOpTag::ADD`, or from
+/// the symbol it is displayed as, e.g. `"+"`, so that `to_string` round
+/// trips.
+macro_rules! impl_from_str {
+    ($type:ty, $table:literal) => {
+        impl std::str::FromStr for $type {
+            type Err = TableError;
+
+            fn from_str(mnemonic: &str) -> Result<Self, Self::Err> {
+                Self::iter()
+                    .find(|tag| {
+                        let symbol: &'static str = (*tag).into();
+                        tag.mnemonic() == mnemonic || symbol == mnemonic
+                    })
+                    .ok_or_else(|| TableError::UnknownMnemonic {
+                        table: $table,
+                        mnemonic: mnemonic.to_string(),
+                    })
+            }
+        }
+    };
+}
+
+/// Common interface of the tables subcircuits look up into.
+pub trait LookupTable<F: FieldExt> {
+    /// Returns the table columns in lookup order.
+    fn columns(&self) -> Vec<Column<Any>>;
+
+    /// Returns one annotation per column, in the same order as `columns`.
+    fn annotations(&self) -> Vec<String>;
+
+    /// Returns the expressions to use on the table side of a `lookup_any`.
+    fn table_exprs(&self, meta: &mut VirtualCells<'_, F>) -> Vec<Expression<F>> {
+        self.columns()
+            .iter()
+            .map(|&column| meta.query_any(column, Rotation::cur()))
+            .collect()
+    }
+
+    /// Pairs the current row of `advice`, one column per table column in
+    /// lookup order, with `table_exprs`, ready to be returned from a
+    /// `lookup_any`.
+    ///
+    /// Panics if `advice` does not have one column per table column, which
+    /// `zip` would otherwise silently truncate.
+    fn lookup_tuples(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        advice: &[Column<Advice>],
+    ) -> Vec<(Expression<F>, Expression<F>)> {
+        let table_exprs = self.table_exprs(meta);
+        assert_eq!(
+            advice.len(),
+            table_exprs.len(),
+            "one advice column is needed per table column"
+        );
+        advice
+            .iter()
+            .map(|&column| meta.query_advice(column, Rotation::cur()))
+            .zip(table_exprs)
+            .collect()
+    }
+
+    /// Checks that there is exactly one annotation per column.
+    fn validate_shape(&self) -> Result<(), TableError> {
+        let columns = self.columns().len();
+        let annotations = self.annotations().len();
+        if columns != annotations {
+            return Err(TableError::ShapeMismatch {
+                columns,
+                annotations,
+            });
+        }
+        Ok(())
+    }
+
+    /// Names the columns so that `MockProver` failures refer to them.
+    ///
+    /// Panics if `validate_shape` fails, as a mismatch is a bug in the table
+    /// and would otherwise silently leave columns unnamed.
+    fn annotate_columns(&self, cs: &mut ConstraintSystem<F>) {
+        if let Err(err) = self.validate_shape() {
+            panic!("{err}");
+        }
+        self.columns()
+            .iter()
+            .zip(self.annotations().iter())
+            .for_each(|(&column, annotation)| cs.annotate_lookup_any_column(column, || annotation));
+    }
+}
+
+/// A table of `N` fixed columns, loaded from precomputed rows holding one
+/// value per column.
+pub trait PrecomputedFixedTable<const N: usize> {
+    /// Name of the region the rows are loaded in by `load`.
+    const REGION: &'static str;
+
+    /// Returns the columns, in the order of the values of a row.
+    fn fixed_columns(&self) -> [Column<Fixed>; N];
+
+    /// Rejects rows that do not belong in the table, before anything is
+    /// assigned. Accepts every row by default.
+    fn check_rows(&self, _rows: &[[u64; N]]) -> Result<(), TableError> {
+        Ok(())
+    }
+
+    fn load<F: FieldExt>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        rows: Vec<[u64; N]>,
+    ) -> Result<(), TableError> {
+        self.load_named(layouter, Self::REGION, rows)
+    }
+
+    /// Same as `load`, in a region named `name`, e.g. to tell apart two
+    /// tables of the same kind.
+    fn load_named<F: FieldExt>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        name: &str,
+        rows: Vec<[u64; N]>,
+    ) -> Result<(), TableError> {
+        self.check_rows(&rows)?;
+        self.assign_rows(layouter, name, rows.iter().map(|row| row.map(F::from)))
+    }
+
+    /// Assigns `rows`, already encoded into the field, from offset 0 of a
+    /// region named `name`.
+    ///
+    /// Past `MAX_REGION_ROWS` rows, the rows are assigned in chunks of that
+    /// many rows, in regions named `name` followed by the chunk index.
+    /// Lookups only check membership, so the split is invisible to them.
+    fn assign_rows<F: FieldExt>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        name: &str,
+        rows: impl Iterator<Item = [F; N]> + Clone,
+    ) -> Result<(), TableError> {
+        let columns = self.fixed_columns();
+        let num_rows = rows.clone().count();
+        // a single, possibly empty, region for small tables
+        let num_chunks = 1 + num_rows.saturating_sub(1) / MAX_REGION_ROWS;
+        for chunk_index in 0..num_chunks {
+            let chunk = rows
+                .clone()
+                .skip(chunk_index * MAX_REGION_ROWS)
+                .take(MAX_REGION_ROWS);
+            let region_name = if num_chunks == 1 {
+                name.to_string()
+            } else {
+                format!("{name} {chunk_index}")
+            };
+            log_debug!("{region_name}: {} rows", chunk.clone().count());
+            layouter.assign_region(
+                || region_name.clone(),
+                |mut region| {
+                    for (offset, row) in chunk.clone().enumerate() {
+                        for (column, value) in columns.iter().zip(row) {
+                            region.assign_fixed(
+                                || name,
+                                *column,
+                                offset,
+                                || Value::known(value),
+                            )?;
+                        }
+                    }
+                    Ok(())
+                },
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl_expr!(BinOpTag);
+impl_try_from_int!(BinOpTag, "binop");
+impl_from_str!(BinOpTag, "binop");
+
+/// Default row cap of `try_generate_binop_table`. The full table at byte
+/// width, the widest `BinOpConfig` accepts, takes 1.4M rows.
+pub const MAX_BINOP_ROWS: usize = 1 << 22;
+
+/// Same as `generate_binop_table`, but returns `TableTooLarge` instead of
+/// trying to allocate the rows if there would be more than `max_rows`,
+/// e.g. the 369M rows of range 12.
+pub fn try_generate_binop_table(
+    lhs_range: u32,
+    rhs_range: u32,
+    max_rows: usize,
+) -> Result<Vec<[u64; 4]>, TableError> {
+    let estimated_rows = estimated_binop_rows(lhs_range, rhs_range);
+    if estimated_rows > max_rows as u128 {
+        return Err(TableError::TableTooLarge { estimated_rows });
+    }
+    Ok(generate_binop_table(lhs_range, rhs_range))
+}
+
+/// Converts a `bits`-bit word into the field, rejecting a `value` that does
+/// not fit in `bits` bits instead of truncating it.
+///
+/// `bits` must also stay below the capacity of the field, so that distinct
+/// words map to distinct field elements.
+pub fn to_field<F: FieldExt>(value: u128, bits: u32) -> Result<F, TableError> {
+    let fits = bits <= F::CAPACITY && (bits >= u128::BITS || value >> bits == 0);
+    if !fits {
+        return Err(TableError::ValueTooWide { value, bits });
+    }
+    Ok(F::from_u128(value))
+}
+
+/// Encodes the `res` of a table row into the field.
+///
+/// A negative signed result `-k` is encoded as `p - k`, i.e. `-F::from(k)`;
+/// everything else is encoded as is.
+pub fn encode_binop_res<F: FieldExt>(tag: BinOpTag, res: u64) -> F {
+    Encoded::from_res(tag, res).inner()
+}
+
+/// A value as the circuit holds it, as opposed to the `u64` word it
+/// stands for.
+///
+/// Words are put in the field as is, except for signed ones, whose negative
+/// values `-k` become `p - k`. Going through one of the constructors keeps
+/// a two's complement `u64` from being taken for an encoded value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Encoded<F: FieldExt>(F);
+
+impl<F: FieldExt> Encoded<F> {
+    pub fn from_unsigned(value: u64) -> Self {
+        Self(F::from(value))
+    }
+
+    pub fn from_signed(value: i64) -> Self {
+        if value < 0 {
+            Self(-F::from(value.unsigned_abs()))
+        } else {
+            Self(F::from(value as u64))
+        }
+    }
+
+    /// The `res` of a `tag` row, signed if the op has a signed result, see
+    /// `BinOpTag::has_signed_result`.
+    pub fn from_res(tag: BinOpTag, res: u64) -> Self {
+        if tag.has_signed_result() {
+            Self::from_signed(res as i64)
+        } else {
+            Self::from_unsigned(res)
+        }
+    }
+
+    pub fn inner(self) -> F {
+        self.0
+    }
+}
+
+/// A row of the binop table, with named fields instead of positions.
+///
+/// Values stay `u64` like the raw rows: the rows of
+/// `generate_wide_binop_table_for` do not fit the operand width, and `SSUB`
+/// results are two's complement `i64`s. `is_error` mirrors the table column of the same name.
+#[derive(Clone, Copy, Debug)]
+pub struct BinopRow {
+    pub tag: BinOpTag,
+    pub lhs: u64,
+    pub rhs: u64,
+    pub res: u64,
+    pub is_error: bool,
+}
+
+impl BinopRow {
+    /// The raw `[tag, lhs, rhs, res]` row, as taken by
+    /// `BinaryOperationTable::load`.
+    pub fn to_row(&self) -> [u64; 4] {
+        [self.tag as u64, self.lhs, self.rhs, self.res]
+    }
+}
+
+impl TryFrom<[u64; 4]> for BinopRow {
+    type Error = TableError;
+
+    fn try_from([tag, lhs, rhs, res]: [u64; 4]) -> Result<Self, Self::Error> {
+        let tag = u8::try_from(tag)
+            .map_err(|_| TableError::InvalidTag {
+                table: "binop",
+                value: tag,
+            })
+            .and_then(BinOpTag::try_from)?;
+        Ok(Self {
+            tag,
+            lhs,
+            rhs,
+            res,
+            is_error: tag.is_error(rhs),
+        })
+    }
+}
+
+/// Same rows as `generate_binop_table`, as `BinopRow`s.
+pub fn generate_binop_rows(lhs_range: u32, rhs_range: u32) -> Vec<BinopRow> {
+    generate_binop_table(lhs_range, rhs_range)
+        .into_iter()
+        // every generated tag is a `BinOpTag`
+        .map(|row| BinopRow::try_from(row).unwrap())
+        .collect()
+}
+
+/// `res` of the rows of `generate_binop_table`, indexed by `(tag, lhs, rhs)`.
+///
+/// Lets witness builders find a result in constant time, consistent with
+/// the rows loaded in-circuit. Operands fit in a byte since table widths are
+/// at most 8 bits; results are kept as `u64`, for the two's complement
+/// `SSUB` results and the exact rows of `generate_wide_binop_table_for`.
+#[derive(Clone, Debug, Default)]
+pub struct BinopLookupMap {
+    rows: HashMap<(u8, u8, u8), u64>,
+}
+
+impl BinopLookupMap {
+    pub fn new(range: u32) -> Self {
+        Self::from_rows(generate_binop_table(range, range))
+    }
+
+    pub fn from_rows(rows: impl IntoIterator<Item = [u64; 4]>) -> Self {
+        let rows = rows
+            .into_iter()
+            .map(|[tag, lhs, rhs, res]| ((tag as u8, lhs as u8, rhs as u8), res))
+            .collect();
+        Self { rows }
+    }
+
+    pub fn get(&self, tag: u8, lhs: u8, rhs: u8) -> Option<u64> {
+        self.rows.get(&(tag, lhs, rhs)).copied()
+    }
+
+    /// Whether `[tag, lhs, rhs, res]` is one of the rows, i.e. whether a
+    /// lookup of it into the loaded table would pass.
+    ///
+    /// Meant for tests, to assert membership without running a prover and
+    /// reading a failure as "not in the table".
+    pub fn contains_row(&self, [tag, lhs, rhs, res]: [u64; 4]) -> bool {
+        match (u8::try_from(tag), u8::try_from(lhs), u8::try_from(rhs)) {
+            (Ok(tag), Ok(lhs), Ok(rhs)) => self.get(tag, lhs, rhs) == Some(res),
+            _ => false,
+        }
+    }
+}
+
+/// Rows a table of `rows` rows takes once padded: the rows and the single
+/// padding row disabled lookups reduce to.
+///
+/// Not rounded up to a power of two, so that a table of `2^m` rows still
+/// fits the `2^(m + 1)` rows of the smallest `k` that can hold it, padding
+/// and blinding rows included.
+pub fn padded_len(rows: usize) -> usize {
+    rows + 1
+}
+
+#[derive(Clone, Debug)]
+pub struct BinaryOperationTable {
+    pub tag: Column<Fixed>,
+    pub lhs: Column<Fixed>,
+    pub rhs: Column<Fixed>,
+    pub res: Column<Fixed>,
+    /// 1 on the rows where the op traps, see `BinOpTag::is_error`.
+    pub is_error: Column<Fixed>,
+    /// 1 on the rows where the op carries out of a `bits`-bit word, see
+    /// `BinOpTag::carry`.
+    pub carry: Column<Fixed>,
+    /// Operand width of the loaded rows.
+    pub bits: u32,
+    /// Row the padding is made of, and that disabled inputs reduce to in
+    /// `lookup_tuples_gated`, see `with_sentinel`. All zero by default.
+    pub sentinel: Option<[u64; 6]>,
+    /// Ops whose rows `load` accepts, see `supports`. All ops by default.
+    pub supported_ops: HashSet<BinOpTag>,
+}
+
+impl BinaryOperationTable {
+    pub fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>, bits: u32) -> Self {
+        Self {
+            tag: meta.fixed_column(),
+            lhs: meta.fixed_column(),
+            rhs: meta.fixed_column(),
+            res: meta.fixed_column(),
+            is_error: meta.fixed_column(),
+            carry: meta.fixed_column(),
+            bits,
+            sentinel: None,
+            supported_ops: BinOpTag::iter().collect(),
+        }
+    }
+
+    /// Restricts the rows `load` accepts to the ones of `ops`, so that
+    /// witness builders can tell from `supports` which ops the loaded table
+    /// can prove.
+    pub fn with_supported_ops(mut self, ops: &[BinOpTag]) -> Self {
+        self.supported_ops = ops.iter().copied().collect();
+        self
+    }
+
+    /// Whether the table may hold rows of `tag`. A row of any other op is
+    /// rejected by `load`, so looking it up can only fail.
+    pub fn supports(&self, tag: BinOpTag) -> bool {
+        self.supported_ops.contains(&tag)
+    }
+
+    /// Pads the table with `sentinel`, a `[tag, lhs, rhs, res, is_error,
+    /// carry]` row, instead of zeros.
+    ///
+    /// The zero row then no longer needs to be in the table, so a disabled
+    /// row can only match the sentinel, provided it is looked up through
+    /// `lookup_tuples_gated`. Pick a tag that is no `BinOpTag`, so that the
+    /// sentinel is no valid operation either.
+    pub fn with_sentinel(mut self, sentinel: [u64; 6]) -> Self {
+        self.sentinel = Some(sentinel);
+        self
+    }
+
+    /// Same tuples as `lookup_tuples`, with the inputs multiplied by
+    /// `q_enable`, and set to the sentinel where `q_enable` is 0 if the
+    /// table has one.
+    pub fn lookup_tuples_gated<F: FieldExt>(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        q_enable: Expression<F>,
+        advice: &[Column<Advice>],
+    ) -> Vec<(Expression<F>, Expression<F>)> {
+        let one = Expression::Constant(F::one());
+        self.lookup_tuples(meta, advice)
+            .into_iter()
+            .enumerate()
+            .map(|(i, (input, table))| {
+                let input = q_enable.clone() * input;
+                let input = match self.sentinel {
+                    Some(sentinel) => {
+                        let sentinel = Expression::Constant(F::from(sentinel[i]));
+                        input + (one.clone() - q_enable.clone()) * sentinel
+                    }
+                    None => input,
+                };
+                (input, table)
+            })
+            .collect()
+    }
+
+    /// Same rows as `generate_binop_table`, sorted lexicographically and
+    /// without duplicates.
+    ///
+    /// Lookups only check membership, so the accepted tuples are unchanged;
+    /// the sorted table can be binary searched on the host.
+    pub fn precompute_deduped(range: u32) -> Vec<[u64; 4]> {
+        let mut rows = generate_binop_table(range, range);
+        rows.sort_unstable();
+        rows.dedup();
+        rows
+    }
+
+    /// Loads `precomputed`, either rows from `generate_binop_table`, a
+    /// `PrecomputedBinopTable` or any iterator over rows.
+    ///
+    /// The rows are not collected: the iterator is cloned and walked once
+    /// per pass the floor planner makes over the region, plus three times
+    /// to check the tags, valid and supported, and that the operands fit in
+    /// `bits` bits, and once to count the rows.
+    ///
+    /// A single zero row, or the sentinel row, is added after the loaded
+    /// rows for disabled lookups to match, see `padded_len`.
+    ///
+    /// An empty `precomputed` is not an error: the table then holds the
+    /// single zero row, so the columns are always assigned and only enabled
+    /// lookups fail, at verification.
+    pub fn load<F: FieldExt, I>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        precomputed: I,
+    ) -> Result<(), TableError>
+    where
+        I: IntoIterator<Item = [u64; 4]>,
+        I::IntoIter: Clone,
+    {
+        self.load_named(layouter, Self::REGION, precomputed)
+    }
+
+    /// Same as `load`, but first recomputes every row with `BinOpTag::eval`
+    /// and rejects the table at the first row whose `res` differs, or whose
+    /// tag is not even a byte, for rows from an untrusted source such as a
+    /// table read back from disk.
+    pub fn load_verified<F: FieldExt, I>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        precomputed: I,
+    ) -> Result<(), TableError>
+    where
+        I: IntoIterator<Item = [u64; 4]>,
+        I::IntoIter: Clone,
+    {
+        let precomputed = precomputed.into_iter();
+        // Undefined byte tags and operands too wide for `eval` are left for
+        // `load` to report.
+        let bound = max_value(self.bits);
+        for (row, [tag, lhs, rhs, res]) in precomputed.clone().enumerate() {
+            let tag = u8::try_from(tag).map_err(|_| TableError::InconsistentRow { row })?;
+            let inconsistent = matches!(
+                BinOpTag::try_from(tag),
+                Ok(op) if lhs <= bound && rhs <= bound && op.eval(lhs, rhs, self.bits).0 != res
+            );
+            if inconsistent {
+                return Err(TableError::InconsistentRow { row });
+            }
+        }
+        self.load(layouter, precomputed)
+    }
+
+    /// Same as `load`, in a region named `name`.
+    pub fn load_named<F: FieldExt, I>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        name: &str,
+        precomputed: I,
+    ) -> Result<(), TableError>
+    where
+        I: IntoIterator<Item = [u64; 4]>,
+        I::IntoIter: Clone,
+    {
+        let precomputed = precomputed.into_iter();
+        check_tags::<BinOpTag>("binop", precomputed.clone().map(|v| v[0]))?;
+        let unsupported = precomputed
+            .clone()
+            .map(|[tag, ..]| tag)
+            .find(|tag| !self.supports(BinOpTag::try_from(*tag as u8).unwrap()));
+        if let Some(tag) = unsupported {
+            return Err(TableError::OpNotAllowed { tag });
+        }
+        let too_wide = precomputed
+            .clone()
+            .flat_map(|[_, lhs, rhs, _]| [lhs, rhs])
+            .find(|value| *value > max_value(self.bits));
+        if let Some(value) = too_wide {
+            return Err(TableError::ValueTooWide {
+                value: value.into(),
+                bits: self.bits,
+            });
+        }
+        let num_rows = precomputed.clone().count();
+        let rows = precomputed.map(|[tag, lhs, rhs, res]| {
+            // The tags were checked above.
+            let op = BinOpTag::try_from(tag as u8).unwrap();
+            let is_error = op.is_error(rhs) as u64;
+            let carry = op.carry(lhs, rhs, self.bits) as u64;
+            let res = encode_binop_res::<F>(op, res);
+            [
+                F::from(tag),
+                F::from(lhs),
+                F::from(rhs),
+                res,
+                F::from(is_error),
+                F::from(carry),
+            ]
+        });
+        let padding_row = self.sentinel.map_or([F::zero(); 6], |row| row.map(F::from));
+        let padding = std::iter::repeat(padding_row).take(padded_len(num_rows) - num_rows);
+        self.assign_rows(layouter, name, rows.chain(padding))
+    }
+}
+
+impl PrecomputedFixedTable<6> for BinaryOperationTable {
+    const REGION: &'static str = "binop table";
+
+    fn fixed_columns(&self) -> [Column<Fixed>; 6] {
+        [
+            self.tag,
+            self.lhs,
+            self.rhs,
+            self.res,
+            self.is_error,
+            self.carry,
+        ]
+    }
+}
+
+impl<F: FieldExt> LookupTable<F> for BinaryOperationTable {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.tag.into(),
+            self.lhs.into(),
+            self.rhs.into(),
+            self.res.into(),
+            self.is_error.into(),
+            self.carry.into(),
+        ]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("tag"),
+            String::from("lhs"),
+            String::from("rhs"),
+            String::from("res"),
+            String::from("is_error"),
+            String::from("carry"),
+        ]
+    }
+}
+
+/// `[tag, lhs, rhs, res]` rows like `BinaryOperationTable`'s, in advice
+/// columns, so that they are part of the witness rather than of the
+/// verifying key and can change from one proof to the next.
+///
+/// The rows are chosen by the prover: a lookup only proves that its input
+/// is one of them, not that they are correct. Either bind them to public
+/// values, or only use the table where that is enough.
+#[derive(Clone, Debug)]
+pub struct DynamicBinaryOperationTable {
+    pub tag: Column<Advice>,
+    pub lhs: Column<Advice>,
+    pub rhs: Column<Advice>,
+    pub res: Column<Advice>,
+}
+
+impl DynamicBinaryOperationTable {
+    pub fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            tag: meta.advice_column(),
+            lhs: meta.advice_column(),
+            rhs: meta.advice_column(),
+            res: meta.advice_column(),
+        }
+    }
+
+    /// Assigns `rows`, followed by a zero row for the disabled lookup
+    /// inputs. `res` is encoded with `encode_binop_res`.
+    pub fn load<F: FieldExt>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        rows: &[[u64; 4]],
+    ) -> Result<(), TableError> {
+        check_tags::<BinOpTag>("dynamic binop", rows.iter().map(|row| row[0]))?;
+        log_debug!("dynamic binop table: {} rows", rows.len());
+        let columns = [self.tag, self.lhs, self.rhs, self.res];
+        layouter.assign_region(
+            || "dynamic binop table",
+            |mut region| {
+                let values = rows.iter().map(|&[tag, lhs, rhs, res]| {
+                    // The tags were checked above.
+                    let op = BinOpTag::try_from(tag as u8).unwrap();
+                    let res = encode_binop_res::<F>(op, res);
+                    [F::from(tag), F::from(lhs), F::from(rhs), res]
+                });
+                let zero = std::iter::once([F::zero(); 4]);
+                for (offset, row) in values.chain(zero).enumerate() {
+                    for (column, value) in columns.iter().zip(row) {
+                        region.assign_advice(
+                            || "dynamic binop",
+                            *column,
+                            offset,
+                            || Value::known(value),
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )?;
+        Ok(())
+    }
+}
+
+impl<F: FieldExt> LookupTable<F> for DynamicBinaryOperationTable {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.tag.into(),
+            self.lhs.into(),
+            self.rhs.into(),
+            self.res.into(),
+        ]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("tag"),
+            String::from("lhs"),
+            String::from("rhs"),
+            String::from("res"),
+        ]
+    }
+}
+
+/// The defined `BinOpTag` discriminants, as `(1, tag, swapped)` rows:
+/// `swapped` is 0, and also 1 for the commutative tags, see
+/// `BinOpTag::is_commutative`.
+///
+/// The leading 1 tells the rows apart from unassigned ones, so that a tag
+/// of 0, which is not defined, is rejected too.
+#[derive(Clone, Debug)]
+pub struct BinOpTagTable {
+    pub enabled: Column<Fixed>,
+    pub tag: Column<Fixed>,
+    /// Whether the operands of a row of the tag may come swapped.
+    pub swapped: Column<Fixed>,
+}
+
+impl BinOpTagTable {
+    pub fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            enabled: meta.fixed_column(),
+            tag: meta.fixed_column(),
+            swapped: meta.fixed_column(),
+        }
+    }
+
+    /// `(tag, swapped)` of every row.
+    fn rows() -> impl Iterator<Item = (BinOpTag, bool)> {
+        BinOpTag::iter().map(|tag| (tag, false)).chain(
+            BinOpTag::iter()
+                .filter(BinOpTag::is_commutative)
+                .map(|tag| (tag, true)),
+        )
+    }
+
+    pub fn num_rows() -> usize {
+        Self::rows().count()
+    }
+
+    pub fn load<F: FieldExt>(&self, layouter: &mut impl Layouter<F>) -> Result<(), TableError> {
+        log_debug!("binop tag table: {} rows", Self::num_rows());
+        layouter.assign_region(
+            || "binop tag table",
+            |mut region| {
+                for (offset, (tag, swapped)) in Self::rows().enumerate() {
+                    let row = [
+                        (self.enabled, 1),
+                        (self.tag, tag as u64),
+                        (self.swapped, swapped as u64),
+                    ];
+                    for (column, value) in row {
+                        region.assign_fixed(
+                            || "binop tag",
+                            column,
+                            offset,
+                            || Value::known(F::from(value)),
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )?;
+        Ok(())
+    }
+
+    /// Registers a lookup proving that `tag` is a defined `BinOpTag` on the
+    /// rows where `q_enable` is 1, and that `swapped` is boolean, and 0
+    /// unless the tag is commutative. It must be 0 on the other rows.
+    pub fn lookup_tag<F: FieldExt>(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        name: &'static str,
+        q_enable: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        tag: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        swapped: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+    ) {
+        meta.lookup_any(name, |meta| {
+            let q_enable = q_enable(meta);
+            let tag_expr = tag(meta);
+            let swapped_expr = swapped(meta);
+            vec![
+                (
+                    q_enable.clone(),
+                    meta.query_fixed(self.enabled, Rotation::cur()),
+                ),
+                (
+                    q_enable.clone() * tag_expr,
+                    meta.query_fixed(self.tag, Rotation::cur()),
+                ),
+                (
+                    q_enable * swapped_expr,
+                    meta.query_fixed(self.swapped, Rotation::cur()),
+                ),
+            ]
+        });
+    }
+}
+
+impl<F: FieldExt> LookupTable<F> for BinOpTagTable {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![self.enabled.into(), self.tag.into(), self.swapped.into()]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("enabled"),
+            String::from("tag"),
+            String::from("swapped"),
+        ]
+    }
+}
+
+/// Results of the comparison ops, from the order of their operands.
+///
+/// A lookup into this table replaces the `2^(2 * range)` rows a comparison
+/// op would take in the `BinaryOperationTable`.
+#[derive(Clone, Debug)]
+pub struct ComparisonTable {
+    pub tag: Column<Fixed>,
+    pub ge: Column<Fixed>,
+    pub eq: Column<Fixed>,
+    pub res: Column<Fixed>,
+}
+
+impl ComparisonTable {
+    pub fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            tag: meta.fixed_column(),
+            ge: meta.fixed_column(),
+            eq: meta.fixed_column(),
+            res: meta.fixed_column(),
+        }
+    }
+
+    pub fn load<F: FieldExt>(&self, layouter: &mut impl Layouter<F>) -> Result<(), TableError> {
+        let columns = [self.tag, self.ge, self.eq, self.res];
+        log_debug!(
+            "comparison table: {} rows",
+            generate_comparison_table().len()
+        );
+        layouter.assign_region(
+            || "comparison table",
+            |mut region| {
+                for (offset, row) in generate_comparison_table().into_iter().enumerate() {
+                    for (column, value) in columns.iter().zip(row) {
+                        region.assign_fixed(
+                            || "comparison",
+                            *column,
+                            offset,
+                            || Value::known(F::from(value)),
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )?;
+        Ok(())
+    }
+}
+
+impl<F: FieldExt> LookupTable<F> for ComparisonTable {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.tag.into(),
+            self.ge.into(),
+            self.eq.into(),
+            self.res.into(),
+        ]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("tag"),
+            String::from("ge"),
+            String::from("eq"),
+            String::from("res"),
+        ]
+    }
+}
+
+impl_expr!(UnaryOpTag);
+impl_try_from_int!(UnaryOpTag, "unaryop");
+impl_from_str!(UnaryOpTag, "unaryop");
+
+#[derive(Clone, Debug)]
+pub struct UnaryOperationTable {
+    pub tag: Column<Fixed>,
+    pub operand: Column<Fixed>,
+    pub res: Column<Fixed>,
+}
+
+impl UnaryOperationTable {
+    pub fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            tag: meta.fixed_column(),
+            operand: meta.fixed_column(),
+            res: meta.fixed_column(),
+        }
+    }
+}
+
+impl PrecomputedFixedTable<3> for UnaryOperationTable {
+    const REGION: &'static str = "unaryop table";
+
+    fn fixed_columns(&self) -> [Column<Fixed>; 3] {
+        [self.tag, self.operand, self.res]
+    }
+
+    fn check_rows(&self, rows: &[[u64; 3]]) -> Result<(), TableError> {
+        check_tags::<UnaryOpTag>("unaryop", rows.iter().map(|v| v[0]))
+    }
+}
+
+impl<F: FieldExt> LookupTable<F> for UnaryOperationTable {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![self.tag.into(), self.operand.into(), self.res.into()]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("tag"),
+            String::from("operand"),
+            String::from("value"),
+        ]
+    }
+}
+
+impl_expr!(BlockExitTag);
+impl_try_from_int!(BlockExitTag, "blockexit");
+impl_from_str!(BlockExitTag, "blockexit");
+
+#[derive(Clone, Debug)]
+pub struct BlockExitTable {
+    pub tag: Column<Fixed>,
+    pub cond: Column<Fixed>,
+    pub from: Column<Fixed>,
+    pub to: Column<Fixed>,
+}
+
+impl BlockExitTable {
+    pub fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            tag: meta.fixed_column(),
+            cond: meta.fixed_column(),
+            from: meta.fixed_column(),
+            to: meta.fixed_column(),
+        }
+    }
+
+    pub fn load<F: FieldExt>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        precomputed: Vec<[u64; 4]>,
+    ) -> Result<(), TableError> {
+        self.load_named(layouter, "blockexit table", precomputed)
+    }
+
+    /// Same as `load`, in a region named `name`.
+    pub fn load_named<F: FieldExt>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        name: &str,
+        precomputed: Vec<[u64; 4]>,
+    ) -> Result<(), TableError> {
+        check_tags::<BlockExitTag>("blockexit", precomputed.iter().map(|v| v[0]))?;
+        log_debug!("{name}: {} rows", precomputed.len());
+        layouter.assign_region(
+            || name,
+            |mut region| {
+                for (offset, v) in precomputed.iter().enumerate() {
+                    let [tag, cond, from, to] = *v;
+                    region.assign_fixed(
+                        || "tag",
+                        self.tag,
+                        offset,
+                        || Value::known(F::from(tag)),
+                    )?;
+                    region.assign_fixed(
+                        || "cond",
+                        self.cond,
+                        offset,
+                        || Value::known(F::from(cond)),
+                    )?;
+                    region.assign_fixed(
+                        || "from",
+                        self.from,
+                        offset,
+                        || Value::known(F::from(from)),
+                    )?;
+                    region.assign_fixed(|| "to", self.to, offset, || Value::known(F::from(to)))?;
+                }
+                Ok(())
+            },
+        )?;
+        Ok(())
+    }
+}
+
+impl<F: FieldExt> LookupTable<F> for BlockExitTable {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![
+            self.tag.into(),
+            self.cond.into(),
+            self.from.into(),
+            self.to.into(),
+        ]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("tag"),
+            String::from("cond"),
+            String::from("from"),
+            String::from("to"),
+        ]
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CallTable {
+    pub from: Column<Fixed>,
+    pub to: Column<Fixed>,
+}
+
+impl CallTable {
+    pub fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            from: meta.fixed_column(),
+            to: meta.fixed_column(),
+        }
+    }
+
+    /// Loads `(caller_block, callee_block)` edges.
+    pub fn load<F: FieldExt>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        precomputed: Vec<[u64; 2]>,
+    ) -> Result<(), TableError> {
+        self.load_named(layouter, "call table", precomputed)
+    }
+
+    /// Same as `load`, in a region named `name`.
+    pub fn load_named<F: FieldExt>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        name: &str,
+        precomputed: Vec<[u64; 2]>,
+    ) -> Result<(), TableError> {
+        log_debug!("{name}: {} rows", precomputed.len());
+        layouter.assign_region(
+            || name,
+            |mut region| {
+                for (offset, v) in precomputed.iter().enumerate() {
+                    let [from, to] = *v;
+                    region.assign_fixed(
+                        || "from",
+                        self.from,
+                        offset,
+                        || Value::known(F::from(from)),
+                    )?;
+                    region.assign_fixed(|| "to", self.to, offset, || Value::known(F::from(to)))?;
+                }
+                Ok(())
+            },
+        )?;
+        Ok(())
+    }
+}
+
+impl<F: FieldExt> LookupTable<F> for CallTable {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![self.from.into(), self.to.into()]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![String::from("from"), String::from("to")]
+    }
+}
+
+/// Register file of a VM with `num_registers` registers.
+///
+/// `index` holds exactly `0..num_registers`, so looking up an index with
+/// `lookup_index` fails for any out-of-range register.
+#[derive(Clone, Debug)]
+pub struct RegisterTable {
+    pub index: Column<Fixed>,
+    pub value: Column<Advice>,
+    /// 1 on the rows of the register file, telling them apart from
+    /// unassigned rows, whose index and value also read as 0.
+    pub enabled: Column<Fixed>,
+    pub num_registers: usize,
+}
+
+impl RegisterTable {
+    pub fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>, num_registers: usize) -> Self {
+        let value = meta.advice_column();
+        meta.enable_equality(value);
+        Self {
+            index: meta.fixed_column(),
+            value,
+            enabled: meta.fixed_column(),
+            num_registers,
+        }
+    }
+
+    /// Assigns a single `(index, value)` pair at `offset`.
+    ///
+    /// `value` is a plain first phase advice column, so the value must be
+    /// known when the region is synthesized; it must not depend on challenges.
+    ///
+    /// Returns the value cell, so that it can be copy constrained to a cell
+    /// of another subcircuit, e.g. the `res` of the op writing the register.
+    pub fn assign<F: FieldExt>(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        row: (Value<F>, Value<F>),
+    ) -> Result<AssignedCell<F, F>, Error> {
+        log_trace!("register row {offset}: {row:?}");
+        region.assign_fixed(|| "assign index", self.index, offset, || row.0)?;
+        let value_cell =
+            region.assign_advice(|| "assign register value", self.value, offset, || row.1)?;
+        let enabled = Value::known(F::one());
+        region.assign_fixed(
+            || "assign register enabled",
+            self.enabled,
+            offset,
+            || enabled,
+        )?;
+        Ok(value_cell)
+    }
+
+    /// Assigns one row per register, `values[i]` being the value of register
+    /// `i`. Registers past the end of `values` hold 0.
+    ///
+    /// Returns the value cell of every register, by index, so that later
+    /// regions can copy a register value rather than witness it again.
+    pub fn load<F: FieldExt>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        values: Vec<Value<F>>,
+    ) -> Result<Vec<AssignedCell<F, F>>, TableError> {
+        if values.len() > self.num_registers {
+            return Err(TableError::TooManyRows {
+                table: "register",
+                rows: values.len(),
+                capacity: self.num_registers,
+            });
+        }
+        log_debug!("register table: {} registers", self.num_registers);
+        let cells = layouter.assign_region(
+            || "register table",
+            |mut region| {
+                (0..self.num_registers)
+                    .map(|offset| {
+                        let index = Value::known(F::from(offset as u64));
+                        let value = values
+                            .get(offset)
+                            .copied()
+                            .unwrap_or_else(|| Value::known(F::zero()));
+                        self.assign(&mut region, offset, (index, value))
+                    })
+                    .collect()
+            },
+        )?;
+        Ok(cells)
+    }
+
+    /// Registers a lookup proving that `index` is a register index, i.e. lies
+    /// in `0..num_registers`.
+    pub fn lookup_index<F: FieldExt>(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        name: &'static str,
+        index: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+    ) {
+        meta.lookup_any(name, |meta| {
+            let index_expr = index(meta);
+            vec![(index_expr, meta.query_fixed(self.index, Rotation::cur()))]
+        });
+    }
+
+    /// Tuples proving that register `index` holds `value` on the rows where
+    /// `q_enable` is 1, for a `lookup_any`.
+    ///
+    /// As in `assert_reserved`, the tuple is matched against the enabled
+    /// rows only: a disabled input reduces to `(0, 0, 0)`, which the
+    /// unassigned rows provide, but an enabled read of `(0, 0)` must find
+    /// register 0 holding 0.
+    pub fn read_tuples<F: FieldExt>(
+        &self,
+        meta: &mut VirtualCells<'_, F>,
+        q_enable: Expression<F>,
+        index: Expression<F>,
+        value: Expression<F>,
+    ) -> Vec<(Expression<F>, Expression<F>)> {
+        let inputs = vec![q_enable.clone() * index, q_enable.clone() * value, q_enable];
+        inputs.into_iter().zip(self.table_exprs(meta)).collect()
+    }
+
+    /// Registers one lookup per `(index, value)` pair of `reserved`, proving
+    /// that register `index` holds `value`, e.g. `(0, 0)` for a zero
+    /// register.
+    ///
+    /// The pair is looked up among the enabled rows only, so that the
+    /// unassigned rows, which read as `(0, 0)`, do not satisfy it.
+    pub fn assert_reserved<F: FieldExt>(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        reserved: &[(u64, u64)],
+    ) {
+        for &(index, value) in reserved {
+            meta.lookup_any("reserved register", |meta| {
+                let enabled = meta.query_fixed(self.enabled, Rotation::cur());
+                let index_expr = meta.query_fixed(self.index, Rotation::cur());
+                let value_expr = meta.query_advice(self.value, Rotation::cur());
+                vec![
+                    (Expression::Constant(F::from(index)), index_expr),
+                    (Expression::Constant(F::from(value)), value_expr),
+                    (Expression::Constant(F::one()), enabled),
+                ]
+            });
+        }
+    }
+}
+
+// The columns include `enabled`, so that lookups through `table_exprs` tell
+// the register file apart from unassigned rows, see `read_tuples`.
+impl<F: FieldExt> LookupTable<F> for RegisterTable {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![self.index.into(), self.value.into(), self.enabled.into()]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("index"),
+            String::from("value"),
+            String::from("enabled"),
+        ]
+    }
+}
+
+/// The values `0..(1 << bits)`, to prove that an advice value fits in `bits`
+/// bits.
+#[derive(Clone, Debug)]
+pub struct RangeCheckTable {
+    pub value: Column<Fixed>,
+    pub bits: u32,
+}
+
+impl RangeCheckTable {
+    pub fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>, bits: u32) -> Self {
+        Self {
+            value: meta.fixed_column(),
+            bits,
+        }
+    }
+
+    pub fn load<F: FieldExt>(&self, layouter: &mut impl Layouter<F>) -> Result<(), TableError> {
+        log_debug!("range check table: {} rows", 1u64 << self.bits);
+        layouter.assign_region(
+            || "range check table",
+            |mut region| {
+                for value in 0..=max_value(self.bits) {
+                    region.assign_fixed(
+                        || "value",
+                        self.value,
+                        value as usize,
+                        || Value::known(F::from(value)),
+                    )?;
+                }
+                Ok(())
+            },
+        )?;
+        Ok(())
+    }
+
+    /// Registers a lookup proving that `value` lies in `0..(1 << bits)`.
+    pub fn range_check<F: FieldExt>(
+        &self,
+        meta: &mut ConstraintSystem<F>,
+        name: &'static str,
+        value: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+    ) {
+        meta.lookup_any(name, |meta| {
+            let value_expr = value(meta);
+            vec![(value_expr, meta.query_fixed(self.value, Rotation::cur()))]
+        });
+    }
+}
+
+impl<F: FieldExt> LookupTable<F> for RangeCheckTable {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![self.value.into()]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![String::from("value")]
+    }
+}
+
+/// Memory accesses, one `(address, value, is_write)` row per access.
+///
+/// All columns are advice, as the rows are part of the witness.
+/// `MemoryConfig` takes the accesses in execution order and checks them
+/// against a copy in a second `MemoryTable`, sorted by address and then by
+/// time.
+#[derive(Clone, Debug)]
+pub struct MemoryTable {
+    pub address: Column<Advice>,
+    pub value: Column<Advice>,
+    pub is_write: Column<Advice>,
+}
+
+impl MemoryTable {
+    pub fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            address: meta.advice_column(),
+            value: meta.advice_column(),
+            is_write: meta.advice_column(),
+        }
+    }
+
+    /// Assigns a single `(address, value, is_write)` access at `offset`.
+    pub fn assign<F: FieldExt>(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        row: [Value<F>; 3],
+    ) -> Result<(), Error> {
+        log_trace!("memory row {offset}: {row:?}");
+        region.assign_advice(|| "assign address", self.address, offset, || row[0])?;
+        region.assign_advice(|| "assign memory value", self.value, offset, || row[1])?;
+        region.assign_advice(|| "assign is_write", self.is_write, offset, || row[2])?;
+        Ok(())
+    }
+}
+
+impl<F: FieldExt> LookupTable<F> for MemoryTable {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![self.address.into(), self.value.into(), self.is_write.into()]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("address"),
+            String::from("value"),
+            String::from("is_write"),
+        ]
+    }
+}
+
+/// Stack operations, one `(sp, value, is_push)` row per push or pop, in
+/// execution order.
+///
+/// `sp` is the stack pointer before the operation: a push writes `value` at
+/// slot `sp`, a pop returns the `value` of slot `sp - 1`. All columns are
+/// advice, as the rows are part of the witness.
+#[derive(Clone, Debug)]
+pub struct StackTable {
+    pub sp: Column<Advice>,
+    pub value: Column<Advice>,
+    pub is_push: Column<Advice>,
+}
+
+impl StackTable {
+    pub fn construct<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        Self {
+            sp: meta.advice_column(),
+            value: meta.advice_column(),
+            is_push: meta.advice_column(),
+        }
+    }
+
+    /// Assigns a single `(sp, value, is_push)` operation at `offset`.
+    pub fn assign<F: FieldExt>(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        row: [Value<F>; 3],
+    ) -> Result<(), Error> {
+        log_trace!("stack row {offset}: {row:?}");
+        region.assign_advice(|| "assign sp", self.sp, offset, || row[0])?;
+        region.assign_advice(|| "assign stack value", self.value, offset, || row[1])?;
+        region.assign_advice(|| "assign is_push", self.is_push, offset, || row[2])?;
+        Ok(())
+    }
+}
+
+impl<F: FieldExt> LookupTable<F> for StackTable {
+    fn columns(&self) -> Vec<Column<Any>> {
+        vec![self.sp.into(), self.value.into(), self.is_push.into()]
+    }
+
+    fn annotations(&self) -> Vec<String> {
+        vec![
+            String::from("sp"),
+            String::from("value"),
+            String::from("is_push"),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, halo2curves::bn256::Fr};
+
+    use super::*;
+
+    #[derive(Clone)]
+    struct BlockExitTestConfig {
+        table: BlockExitTable,
+        exit: [Column<Advice>; 4],
+    }
+
+    #[derive(Default)]
+    struct BlockExitTestCircuit {
+        rows: Vec<[u64; 4]>,
+        exit: [u64; 4],
+    }
+
+    impl Circuit<Fr> for BlockExitTestCircuit {
+        type Config = BlockExitTestConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let table = BlockExitTable::construct(meta);
+            let exit = [(); 4].map(|_| meta.advice_column());
+            meta.lookup_any("blockexit lookup", |meta| {
+                let exprs = exit.map(|column| meta.query_advice(column, Rotation::cur()));
+                exprs.into_iter().zip(table.table_exprs(meta)).collect()
+            });
+            BlockExitTestConfig { table, exit }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            config.table.load(&mut layouter, self.rows.clone())?;
+            layouter.assign_region(
+                || "exit",
+                |mut region| {
+                    for (column, v) in config.exit.iter().zip(self.exit) {
+                        region.assign_advice(
+                            || "exit",
+                            *column,
+                            0,
+                            || Value::known(Fr::from(v)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn blockexit_table_load() {
+        let rows = vec![
+            [BlockExitTag::JUMP as u64, 0, 1, 3],
+            [BlockExitTag::CONDJUMP as u64, 1, 3, 5],
+        ];
+        for exit in rows.clone() {
+            let circuit = BlockExitTestCircuit {
+                rows: rows.clone(),
+                exit,
+            };
+            let prover = MockProver::run(6, &circuit, vec![]).unwrap();
+            prover.assert_satisfied();
+        }
+
+        let circuit = BlockExitTestCircuit {
+            rows,
+            exit: [BlockExitTag::JUMP as u64, 0, 1, 4],
+        };
+        let prover = MockProver::run(6, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn call_table_load() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let table = CallTable::construct(&mut meta);
+        meta.create_gate("call table exprs", |meta| {
+            let exprs = table.table_exprs(meta);
+            assert_eq!(exprs.len(), 2);
+            exprs
+        });
+
+        struct CallTableCircuit;
+        impl Circuit<Fr> for CallTableCircuit {
+            type Config = CallTable;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                Self
+            }
+
+            fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+                CallTable::construct(meta)
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fr>,
+            ) -> Result<(), Error> {
+                config.load(&mut layouter, vec![[0, 2], [2, 5]])?;
+                Ok(())
+            }
+        }
+        let prover = MockProver::run(4, &CallTableCircuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[derive(Clone)]
+    struct RegionNamesConfig {
+        blockexit: BlockExitTable,
+        call: CallTable,
+        unaryop: UnaryOperationTable,
+        other_unaryop: UnaryOperationTable,
+    }
+
+    /// Loads each table in a region of its own.
+    struct RegionNamesCircuit;
+
+    impl Circuit<Fr> for RegionNamesCircuit {
+        type Config = RegionNamesConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            RegionNamesConfig {
+                blockexit: BlockExitTable::construct(meta),
+                call: CallTable::construct(meta),
+                unaryop: UnaryOperationTable::construct(meta),
+                other_unaryop: UnaryOperationTable::construct(meta),
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let unop = vec![[UnaryOpTag::NEG as u64, 0, 15]];
+            config.blockexit.load(&mut layouter, vec![])?;
+            config.call.load(&mut layouter, vec![[0, 2]])?;
+            config.unaryop.load(&mut layouter, unop.clone())?;
+            config
+                .other_unaryop
+                .load_named(&mut layouter, "other unaryop table", unop)?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn tables_load_in_distinct_regions() {
+        let prover = MockProver::run(4, &RegionNamesCircuit, vec![]).unwrap();
+        prover.assert_satisfied();
+        // `MockProver` lists its regions, by name, in its debug output
+        let debug = format!("{prover:?}");
+        for name in [
+            "blockexit table",
+            "call table",
+            "unaryop table",
+            "other unaryop table",
+        ] {
+            let region = format!("name: \"{name}\"");
+            assert_eq!(debug.matches(&region).count(), 1, "{name}");
+        }
+    }
+
+    #[derive(Clone)]
+    struct RegisterIndexConfig {
+        table: RegisterTable,
+        index: Column<Advice>,
+    }
+
+    struct RegisterIndexCircuit {
+        values: Vec<u64>,
+        index: u64,
+    }
+
+    impl Circuit<Fr> for RegisterIndexCircuit {
+        type Config = RegisterIndexConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                values: self.values.clone(),
+                index: self.index,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let table = RegisterTable::construct(meta, 4);
+            let index = meta.advice_column();
+            table.lookup_index(meta, "register index", |meta| {
+                meta.query_advice(index, Rotation::cur())
+            });
+            RegisterIndexConfig { table, index }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let values = self
+                .values
+                .iter()
+                .map(|value| Value::known(Fr::from(*value)))
+                .collect();
+            config.table.load(&mut layouter, values)?;
+            layouter.assign_region(
+                || "index",
+                |mut region| {
+                    region.assign_advice(
+                        || "index",
+                        config.index,
+                        0,
+                        || Value::known(Fr::from(self.index)),
+                    )?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn register_table_load() {
+        let circuit = RegisterIndexCircuit {
+            values: vec![0, 7, 3],
+            index: 3,
+        };
+        let prover = MockProver::run(4, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    /// Loads registers `[0, 7, 3]` and copies register 1 into a cell of
+    /// another region holding `self.0`.
+    struct RegisterCopyCircuit(u64);
+
+    impl Circuit<Fr> for RegisterCopyCircuit {
+        type Config = (RegisterTable, Column<Advice>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(self.0)
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let table = RegisterTable::construct(meta, 4);
+            let copy = meta.advice_column();
+            meta.enable_equality(copy);
+            (table, copy)
+        }
+
+        fn synthesize(
+            &self,
+            (table, copy): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let values = [0, 7, 3].map(|value| Value::known(Fr::from(value)));
+            let cells = table.load(&mut layouter, values.to_vec())?;
+            assert_eq!(cells.len(), 4);
+            layouter.assign_region(
+                || "copy",
+                |mut region| {
+                    let cell = region.assign_advice(
+                        || "copy",
+                        copy,
+                        0,
+                        || Value::known(Fr::from(self.0)),
+                    )?;
+                    region.constrain_equal(cell.cell(), cells[1].cell())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn register_cell_copied_across_regions() {
+        let prover = MockProver::run(4, &RegisterCopyCircuit(7), vec![]).unwrap();
+        prover.assert_satisfied();
+        let prover = MockProver::run(4, &RegisterCopyCircuit(3), vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn register_read_tuples_cover_columns() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let table = RegisterTable::construct(&mut meta, 4);
+        let advice = meta.advice_column();
+        let num_tuples = std::cell::Cell::new(0);
+        meta.lookup_any("register read", |meta| {
+            let value = meta.query_advice(advice, Rotation::cur());
+            let q_enable = Expression::Constant(Fr::one());
+            let tuples = table.read_tuples(meta, q_enable, value.clone(), value);
+            num_tuples.set(tuples.len());
+            tuples
+        });
+        assert_eq!(num_tuples.get(), LookupTable::<Fr>::columns(&table).len());
+    }
+
+    #[test]
+    fn register_value_is_first_phase() {
+        // `assign` relies on it, see its doc
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let table = RegisterTable::construct(&mut meta, 4);
+        assert_eq!(table.value.column_type().phase(), 0);
+    }
+
+    /// Keeps the lines logged while the tests run.
+    #[cfg(feature = "log")]
+    struct TestLogger(std::sync::Mutex<Vec<String>>);
+
+    #[cfg(feature = "log")]
+    impl log::Log for TestLogger {
+        fn enabled(&self, _: &log::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record<'_>) {
+            let line = format!("{} {}", record.target(), record.args());
+            self.0.lock().unwrap().push(line);
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn register_table_load_is_logged() {
+        static LOGGER: TestLogger = TestLogger(std::sync::Mutex::new(Vec::new()));
+        log::set_logger(&LOGGER).unwrap();
+        log::set_max_level(log::LevelFilter::Trace);
+
+        let circuit = RegisterIndexCircuit {
+            values: vec![0, 7, 3],
+            index: 3,
+        };
+        MockProver::run(4, &circuit, vec![]).unwrap();
+        let lines = LOGGER.0.lock().unwrap();
+        assert!(
+            lines
+                .iter()
+                .any(|line| line.starts_with("lir::")
+                    && line.ends_with("register table: 4 registers"))
+        );
+        assert!(lines.iter().any(|line| line.contains("register row 1")));
+    }
+
+    #[test]
+    fn register_index_out_of_range() {
+        let circuit = RegisterIndexCircuit {
+            values: vec![0, 7, 3],
+            index: 4,
+        };
+        let prover = MockProver::run(4, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    /// A register table of `values` whose register 0 is reserved to hold 0.
+    struct ReservedRegisterCircuit(Vec<u64>);
+
+    impl Circuit<Fr> for ReservedRegisterCircuit {
+        type Config = RegisterTable;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(self.0.clone())
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let table = RegisterTable::construct(meta, 4);
+            table.assert_reserved(meta, &[(0, 0)]);
+            table
+        }
+
+        fn synthesize(
+            &self,
+            table: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let values = self.0.iter().map(|value| Value::known(Fr::from(*value)));
+            table.load(&mut layouter, values.collect())?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn reserved_register_holds_value() {
+        let prover = MockProver::run(4, &ReservedRegisterCircuit(vec![0, 7, 3]), vec![]).unwrap();
+        prover.assert_satisfied();
+
+        let prover = MockProver::run(4, &ReservedRegisterCircuit(vec![1, 7, 3]), vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[derive(Clone)]
+    struct RangeCheckConfig {
+        table: RangeCheckTable,
+        value: Column<Advice>,
+    }
+
+    struct RangeCheckCircuit(u64);
+
+    impl Circuit<Fr> for RangeCheckCircuit {
+        type Config = RangeCheckConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(self.0)
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let table = RangeCheckTable::construct(meta, 4);
+            let value = meta.advice_column();
+            table.range_check(meta, "range check", |meta| {
+                meta.query_advice(value, Rotation::cur())
+            });
+            RangeCheckConfig { table, value }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+            layouter.assign_region(
+                || "value",
+                |mut region| {
+                    region.assign_advice(
+                        || "value",
+                        config.value,
+                        0,
+                        || Value::known(Fr::from(self.0)),
+                    )?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn range_check_table() {
+        let prover = MockProver::run(6, &RangeCheckCircuit(15), vec![]).unwrap();
+        prover.assert_satisfied();
+        let prover = MockProver::run(6, &RangeCheckCircuit(16), vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    struct BinopLoadCircuit {
+        rows: Vec<[u64; 4]>,
+        /// Loads with `load_verified` rather than `load`.
+        verified: bool,
+        result: RefCell<Option<Result<(), TableError>>>,
+    }
+
+    impl Circuit<Fr> for BinopLoadCircuit {
+        type Config = BinaryOperationTable;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                rows: self.rows.clone(),
+                verified: self.verified,
+                result: RefCell::new(None),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            BinaryOperationTable::construct(meta, 4)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let result = if self.verified {
+                config.load_verified(&mut layouter, self.rows.clone())
+            } else {
+                config.load(&mut layouter, self.rows.clone())
+            };
+            self.result.replace(Some(result));
+            Ok(())
+        }
+    }
+
+    /// Loads `rows` into a fresh binop table and returns what `load` returned.
+    fn load_binop_rows(rows: Vec<[u64; 4]>) -> Result<(), TableError> {
+        run_binop_load(rows, false)
+    }
+
+    /// Same as `load_binop_rows`, with `load_verified`.
+    fn load_binop_rows_verified(rows: Vec<[u64; 4]>) -> Result<(), TableError> {
+        run_binop_load(rows, true)
+    }
+
+    fn run_binop_load(rows: Vec<[u64; 4]>, verified: bool) -> Result<(), TableError> {
+        let circuit = BinopLoadCircuit {
+            rows,
+            verified,
+            result: RefCell::new(None),
+        };
+        MockProver::run(6, &circuit, vec![]).unwrap();
+        circuit.result.into_inner().unwrap()
+    }
+
+    struct IterLoadCircuit;
+
+    impl Circuit<Fr> for IterLoadCircuit {
+        type Config = BinaryOperationTable;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            BinaryOperationTable::construct(meta, 4)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let add = BinOpTag::ADD;
+            let rows = (0..16).map(|y| [add as u64, 1, y, add.eval(1, y, 4).0]);
+            config.load(&mut layouter, rows)?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn binop_table_load_from_iterator() {
+        assert!(MockProver::run(6, &IterLoadCircuit, vec![]).is_ok());
+    }
+
+    /// A 2-bit binop table of the given rows and an ungated lookup of
+    /// `(ADD, 1, 2, 3)`, and of zero on every other row, into it.
+    struct PaddedLookupCircuit(Vec<[u64; 4]>);
+
+    impl Circuit<Fr> for PaddedLookupCircuit {
+        type Config = (BinaryOperationTable, [Column<Advice>; 6]);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(self.0.clone())
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let table = BinaryOperationTable::construct(meta, 2);
+            let advice = [(); 6].map(|_| meta.advice_column());
+            meta.lookup_any("padded binop lookup", |meta| {
+                table.lookup_tuples(meta, &advice)
+            });
+            (table, advice)
+        }
+
+        fn synthesize(
+            &self,
+            (table, advice): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            table.load(&mut layouter, self.0.clone())?;
+            layouter.assign_region(
+                || "ops",
+                |mut region| {
+                    for (column, value) in advice.iter().zip([BinOpTag::ADD as u64, 1, 2, 3, 0, 0])
+                    {
+                        region.assign_advice(
+                            || "op",
+                            *column,
+                            0,
+                            || Value::known(Fr::from(value)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    /// A dynamic binop table of `rows` and an ungated lookup of `op`, and of
+    /// zero on every other row, into it.
+    struct DynamicTableCircuit {
+        rows: Vec<[u64; 4]>,
+        op: [u64; 4],
+    }
+
+    impl Circuit<Fr> for DynamicTableCircuit {
+        type Config = (DynamicBinaryOperationTable, [Column<Advice>; 4]);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                rows: self.rows.clone(),
+                op: self.op,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let table = DynamicBinaryOperationTable::construct(meta);
+            let advice = [(); 4].map(|_| meta.advice_column());
+            meta.lookup_any("dynamic binop lookup", |meta| {
+                table.lookup_tuples(meta, &advice)
+            });
+            (table, advice)
+        }
+
+        fn synthesize(
+            &self,
+            (table, advice): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            table.load(&mut layouter, &self.rows)?;
+            layouter.assign_region(
+                || "ops",
+                |mut region| {
+                    for (column, value) in advice.iter().zip(self.op) {
+                        region.assign_advice(
+                            || "op",
+                            *column,
+                            0,
+                            || Value::known(Fr::from(value)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn dynamic_binop_table_per_proof() {
+        let add = [BinOpTag::ADD as u64, 1, 2, 3];
+        let mul = [BinOpTag::MUL as u64, 3, 3, 9];
+        let run = |rows: Vec<[u64; 4]>, op| {
+            let circuit = DynamicTableCircuit { rows, op };
+            MockProver::run(4, &circuit, vec![]).unwrap().verify()
+        };
+        // the same circuit, with a different table in each proof
+        assert!(run(vec![add], add).is_ok());
+        assert!(run(vec![mul], mul).is_ok());
+        assert!(run(vec![add], mul).is_err());
+    }
+
+    /// Sentinel of `SentinelCircuit`, whose tag is no `BinOpTag`.
+    const SENTINEL: [u64; 6] = [u8::MAX as u64, 0, 0, 0, 0, 0];
+
+    /// A 2-bit `ADD` table with a sentinel, loaded with it or without it, an
+    /// enabled row in the table and a disabled row that is not.
+    struct SentinelCircuit {
+        load_sentinel: bool,
+    }
+
+    impl Circuit<Fr> for SentinelCircuit {
+        type Config = (BinaryOperationTable, [Column<Advice>; 6], Selector);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                load_sentinel: self.load_sentinel,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let table = BinaryOperationTable::construct(meta, 2).with_sentinel(SENTINEL);
+            let advice = [(); 6].map(|_| meta.advice_column());
+            let q_enable = meta.complex_selector();
+            meta.lookup_any("sentinel binop lookup", |meta| {
+                let q_enable = meta.query_selector(q_enable);
+                table.lookup_tuples_gated(meta, q_enable, &advice)
+            });
+            (table, advice, q_enable)
+        }
+
+        fn synthesize(
+            &self,
+            (table, advice, q_enable): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let table = if self.load_sentinel {
+                table
+            } else {
+                BinaryOperationTable {
+                    sentinel: None,
+                    ..table
+                }
+            };
+            table.load(&mut layouter, generate_binop_table_for(&[BinOpTag::ADD], 2))?;
+            layouter.assign_region(
+                || "ops",
+                |mut region| {
+                    q_enable.enable(&mut region, 0)?;
+                    let rows = [
+                        [BinOpTag::ADD as u64, 1, 2, 3, 0, 0],
+                        [BinOpTag::ADD as u64, 1, 2, 0, 0, 0],
+                    ];
+                    for (offset, row) in rows.into_iter().enumerate() {
+                        for (column, value) in advice.iter().zip(row) {
+                            region.assign_advice(
+                                || "op",
+                                *column,
+                                offset,
+                                || Value::known(Fr::from(value)),
+                            )?;
+                        }
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn disabled_rows_look_up_sentinel() {
+        let circuit = SentinelCircuit {
+            load_sentinel: true,
+        };
+        MockProver::run(6, &circuit, vec![])
+            .unwrap()
+            .assert_satisfied();
+
+        // the disabled rows no longer match the zero padding
+        let circuit = SentinelCircuit {
+            load_sentinel: false,
+        };
+        let prover = MockProver::run(6, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    /// A binop table of the 6-bit `ADD` and `MUL` rows and an ungated
+    /// lookup of the given row, and of zero on every other row, into it.
+    struct ChunkedLoadCircuit([u64; 6]);
+
+    impl Circuit<Fr> for ChunkedLoadCircuit {
+        type Config = (BinaryOperationTable, [Column<Advice>; 6]);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(self.0)
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let table = BinaryOperationTable::construct(meta, 6);
+            let advice = [(); 6].map(|_| meta.advice_column());
+            meta.lookup_any("chunked binop lookup", |meta| {
+                table.lookup_tuples(meta, &advice)
+            });
+            (table, advice)
+        }
+
+        fn synthesize(
+            &self,
+            (table, advice): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let rows = generate_binop_table_for(&[BinOpTag::ADD, BinOpTag::MUL], 6);
+            table.load(&mut layouter, rows)?;
+            layouter.assign_region(
+                || "ops",
+                |mut region| {
+                    for (column, value) in advice.iter().zip(self.0) {
+                        region.assign_advice(
+                            || "op",
+                            *column,
+                            0,
+                            || Value::known(Fr::from(value)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn binop_table_load_in_chunks() {
+        let rows = generate_binop_table_for(&[BinOpTag::ADD, BinOpTag::MUL], 6);
+        assert!(rows.len() > MAX_REGION_ROWS);
+        let k = 15;
+        // rows of the first and of the second region
+        let add = [BinOpTag::ADD as u64, 1, 2, 3, 0, 0];
+        let mul = [BinOpTag::MUL as u64, 63, 63, 1, 0, 1];
+        for row in [add, mul] {
+            let prover = MockProver::run(k, &ChunkedLoadCircuit(row), vec![]).unwrap();
+            prover.assert_satisfied();
+        }
+        let missing = [BinOpTag::MUL as u64, 63, 63, 3969, 0, 1];
+        let prover = MockProver::run(k, &ChunkedLoadCircuit(missing), vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn binop_table_padded_with_one_zero_row() {
+        assert_eq!(padded_len(16), 17);
+        assert_eq!(padded_len(15), 16);
+        let circuit = PaddedLookupCircuit(generate_binop_table_for(&[BinOpTag::ADD], 2));
+        let prover = MockProver::run(12, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn lookup_tuples_per_column() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let table = BinaryOperationTable::construct(&mut meta, 4);
+        let advice = [(); 6].map(|_| meta.advice_column());
+        let num_tuples = std::cell::Cell::new(0);
+        meta.lookup_any("binop lookup", |meta| {
+            let tuples = table.lookup_tuples(meta, &advice);
+            num_tuples.set(tuples.len());
+            tuples
+        });
+        assert_eq!(num_tuples.get(), LookupTable::<Fr>::columns(&table).len());
+    }
+
+    #[test]
+    #[should_panic(expected = "one advice column is needed per table column")]
+    fn lookup_tuples_missing_column() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let table = BinaryOperationTable::construct(&mut meta, 4);
+        let advice = [(); 5].map(|_| meta.advice_column());
+        meta.lookup_any("binop lookup", |meta| table.lookup_tuples(meta, &advice));
+    }
+
+    #[test]
+    fn binop_table_load_empty() {
+        assert_eq!(padded_len(0), 1);
+        // the zero row is assigned, only the lookup of the ADD row fails
+        let prover = MockProver::run(6, &PaddedLookupCircuit(vec![]), vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    /// Loads `rows` into a unary table and looks `(NEG, 0, 1)` up in it.
+    struct UnaryLoadCircuit {
+        rows: Vec<[u64; 3]>,
+        result: RefCell<Option<Result<(), TableError>>>,
+    }
+
+    impl Circuit<Fr> for UnaryLoadCircuit {
+        type Config = (UnaryOperationTable, [Column<Advice>; 3]);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                rows: self.rows.clone(),
+                result: RefCell::new(None),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let table = UnaryOperationTable::construct(meta);
+            let advice = [(); 3].map(|_| meta.advice_column());
+            meta.lookup_any("unaryop lookup", |meta| {
+                let inputs = advice.map(|column| meta.query_advice(column, Rotation::cur()));
+                inputs.into_iter().zip(table.table_exprs(meta)).collect()
+            });
+            (table, advice)
+        }
+
+        fn synthesize(
+            &self,
+            (table, advice): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            self.result
+                .replace(Some(table.load(&mut layouter, self.rows.clone())));
+            layouter.assign_region(
+                || "ops",
+                |mut region| {
+                    for (column, value) in advice.iter().zip([UnaryOpTag::NEG as u64, 0, 1]) {
+                        region.assign_advice(
+                            || "op",
+                            *column,
+                            0,
+                            || Value::known(Fr::from(value)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn unaryop_table_load() {
+        let neg = UnaryOpTag::NEG as u64;
+        let run = |rows| {
+            let circuit = UnaryLoadCircuit {
+                rows,
+                result: RefCell::new(None),
+            };
+            let prover = MockProver::run(6, &circuit, vec![]).unwrap();
+            (
+                prover.verify().is_ok(),
+                circuit.result.into_inner().unwrap(),
+            )
+        };
+        assert!(matches!(
+            run(vec![[neg, 0, 1], [neg, 1, 0]]),
+            (true, Ok(()))
+        ));
+        assert!(matches!(run(vec![[neg, 0, 0]]), (false, Ok(()))));
+        assert!(matches!(
+            run(vec![[neg, 0, 1], [0, 0, 1]]),
+            (
+                false,
+                Err(TableError::InvalidTag {
+                    table: "unaryop",
+                    value: 0
+                })
+            )
+        ));
+    }
+
+    #[test]
+    fn binop_table_load_verified() {
+        let add = BinOpTag::ADD as u64;
+        let rows = vec![[add, 1, 2, 3], [add, 2, 3, 99]];
+        // `load` trusts the rows
+        assert!(load_binop_rows(rows.clone()).is_ok());
+        assert!(matches!(
+            load_binop_rows_verified(rows),
+            Err(TableError::InconsistentRow { row: 1 })
+        ));
+        assert!(load_binop_rows_verified(vec![[add, 1, 2, 3], [add, 2, 3, 5]]).is_ok());
+        // operands wider than the 4-bit table are rejected, not evaluated
+        assert!(matches!(
+            load_binop_rows_verified(vec![[add, 16, 1, 17]]),
+            Err(TableError::ValueTooWide { value: 16, bits: 4 })
+        ));
+        assert!(matches!(
+            load_binop_rows_verified(vec![[0, 1, 2, 3]]),
+            Err(TableError::InvalidTag { .. })
+        ));
+        // not ADD, whose tag it truncates to
+        assert!(matches!(
+            load_binop_rows_verified(vec![[add, 1, 2, 3], [256 + add, 2, 3, 5]]),
+            Err(TableError::InconsistentRow { row: 1 })
+        ));
+    }
+
+    #[test]
+    fn binop_table_rejects_invalid_tag() {
+        assert!(load_binop_rows(vec![[BinOpTag::ADD as u64, 1, 2, 3]]).is_ok());
+        assert!(matches!(
+            load_binop_rows(vec![[BinOpTag::ADD as u64, 1, 2, 3], [0, 1, 2, 3]]),
+            Err(TableError::InvalidTag {
+                table: "binop",
+                value: 0
+            })
+        ));
+    }
+
+    #[test]
+    fn tag_try_from_u8() {
+        for tag in BinOpTag::iter() {
+            assert_eq!(BinOpTag::try_from(tag as u8).unwrap() as u8, tag as u8);
+        }
+        for tag in UnaryOpTag::iter() {
+            assert_eq!(UnaryOpTag::try_from(tag as u8).unwrap() as u8, tag as u8);
+        }
+        for tag in BlockExitTag::iter() {
+            assert_eq!(BlockExitTag::try_from(tag as u8).unwrap() as u8, tag as u8);
+        }
+        assert!(BinOpTag::try_from(0u8).is_err());
+        assert!(matches!(
+            UnaryOpTag::try_from(4u8),
+            Err(TableError::InvalidTag {
+                table: "unaryop",
+                value: 4
+            })
+        ));
+    }
+
+    #[test]
+    fn binop_table_row_count() {
+        for range in 1..=4 {
+            let rows = generate_binop_table(range, range);
+            assert_eq!(rows.len(), (1 << range).pow(2) * BinOpTag::iter().count());
+        }
+    }
+
+    #[test]
+    fn binop_table_asymmetric_widths() {
+        let rows = generate_binop_table(8, 3);
+        assert_eq!(rows.len(), (1 << 11) * BinOpTag::iter().count());
+        let shl = BinOpTag::SHL as u64;
+        assert!(rows.contains(&[shl, 0xff, 7, 0x80]));
+        assert!(rows.contains(&[shl, 0x81, 1, 0x02]));
+        // no shift amount past the 3-bit rhs domain
+        assert!(rows.iter().all(|row| row[2] < 8));
+        assert!(!rows.contains(&[shl, 1, 8, 0]));
+        // the word width follows lhs_range, not the narrower rhs_range
+        assert!(!generate_binop_table(3, 3).contains(&[shl, 7, 1, 14]));
+        assert!(generate_binop_table(4, 3).contains(&[shl, 7, 1, 14]));
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn binop_table_parallel_matches_serial() {
+        for range in 1..=4 {
+            assert_eq!(
+                generate_binop_table(range, range),
+                generate_binop_table_serial(range, range)
+            );
+            assert_eq!(
+                generate_binop_table(range + 2, range),
+                generate_binop_table_serial(range + 2, range)
+            );
+        }
+    }
+
+    #[test]
+    fn binop_table_div_by_zero_is_error() {
+        let table = BinopLookupMap::new(4);
+        for op in [BinOpTag::DIV, BinOpTag::MOD] {
+            assert!(table.contains_row([op as u64, 5, 0, 0]));
+            assert!(op.is_error(0));
+            assert!(!op.is_error(1));
+        }
+        assert!(!BinOpTag::ADD.is_error(0));
+    }
+
+    #[test]
+    fn to_field_rejects_wide_values() {
+        assert_eq!(to_field::<Fr>(255, 8).unwrap(), Fr::from(255));
+        assert_eq!(
+            to_field::<Fr>(u128::MAX, 128).unwrap(),
+            Fr::from_u128(u128::MAX)
+        );
+        assert!(matches!(
+            to_field::<Fr>(256, 8),
+            Err(TableError::ValueTooWide {
+                value: 256,
+                bits: 8
+            })
+        ));
+        assert!(matches!(
+            to_field::<Fr>(1 << 64, 64),
+            Err(TableError::ValueTooWide { .. })
+        ));
+    }
+
+    #[test]
+    fn binop_table_wide_results() {
+        // results wrap to the operand byte, unless asked for exact ones
+        let ops = [BinOpTag::MUL, BinOpTag::ADD];
+        let rows = generate_binop_table_for(&ops, 8);
+        assert!(rows.contains(&[BinOpTag::MUL as u64, 255, 255, 1]));
+        assert!(rows.contains(&[BinOpTag::ADD as u64, 255, 255, 254]));
+        let rows = generate_wide_binop_table_for(&ops, 8);
+        assert!(rows.contains(&[BinOpTag::MUL as u64, 200, 200, 40000]));
+        assert!(rows.contains(&[BinOpTag::MUL as u64, 255, 255, 65025]));
+        assert!(rows.contains(&[BinOpTag::ADD as u64, 255, 255, 510]));
+    }
+
+    #[test]
+    fn binop_carry() {
+        assert_eq!(BinOpTag::ADD.eval(255, 1, 8), (0, false));
+        assert!(BinOpTag::ADD.carry(255, 1, 8));
+        assert_eq!(BinOpTag::SUB.eval(0, 1, 8), (255, false));
+        assert!(!BinOpTag::ADD.carry(254, 1, 8));
+        assert!(BinOpTag::MUL.carry(16, 16, 8));
+        assert!(!BinOpTag::MUL.carry(15, 17, 8));
+        assert!(BinOpTag::SUB.carry(0, 1, 8));
+        assert!(!BinOpTag::SUB.carry(1, 1, 8));
+        assert!(!BinOpTag::OR.carry(255, 255, 8));
+    }
+
+    #[test]
+    fn eval_at_word_bounds() {
+        let max = max_value(32);
+        assert_eq!(BinOpTag::MUL.eval(max, max, 32), (1, false));
+        assert_eq!(BinOpTag::MUL.eval_wide(max, max, 32), max * max);
+        assert!(BinOpTag::MUL.carry(max, max, 32));
+        assert_eq!(BinOpTag::ADD.eval(max, max, 32), (max - 1, false));
+        // at range 1 the only bit is the sign bit: 1 is -1
+        assert_eq!(BinOpTag::SLT.eval(1, 0, 1), (1, false));
+        assert_eq!(BinOpTag::SSUB.eval(0, 1, 1), (1, false));
+        // the unsigned ops do not read the sign bit
+        assert_eq!(BinOpTag::ADD.eval(1, 0, 1), (1, false));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "do not fit in 4 bits")]
+    fn eval_rejects_wide_operands() {
+        BinOpTag::ADD.eval(16, 1, 4);
+    }
+
+    #[test]
+    fn binop_table_complements() {
+        let rows = generate_binop_table_for(&[BinOpTag::NAND, BinOpTag::NOR, BinOpTag::XNOR], 8);
+        assert!(rows.contains(&[BinOpTag::NAND as u64, 0xff, 0xff, 0x00]));
+        assert!(rows.contains(&[BinOpTag::NAND as u64, 0xf0, 0x3c, 0xcf]));
+        assert!(rows.contains(&[BinOpTag::NOR as u64, 0x00, 0x00, 0xff]));
+        assert!(rows.contains(&[BinOpTag::NOR as u64, 0xf0, 0x0c, 0x03]));
+        assert!(rows.contains(&[BinOpTag::XNOR as u64, 0xaa, 0xaa, 0xff]));
+        assert!(rows.contains(&[BinOpTag::XNOR as u64, 0xf0, 0x3c, 0x33]));
+        assert!(rows.iter().all(|row| row[3] <= 0xff));
+    }
+
+    #[test]
+    fn binop_rows_fields() {
+        let rows = generate_binop_rows(4, 4);
+        assert_eq!(rows.len(), generate_binop_table(4, 4).len());
+        let row = rows
+            .iter()
+            .find(|row| matches!(row.tag, BinOpTag::DIV) && row.lhs == 7 && row.rhs == 0)
+            .unwrap();
+        assert_eq!(row.res, 0);
+        assert!(row.is_error);
+        assert_eq!(row.to_row(), [BinOpTag::DIV as u64, 7, 0, 0]);
+
+        let row = BinopRow::try_from([BinOpTag::MUL as u64, 15, 15, 225]).unwrap();
+        assert!(matches!(row.tag, BinOpTag::MUL));
+        assert_eq!(
+            (row.lhs, row.rhs, row.res, row.is_error),
+            (15, 15, 225, false)
+        );
+        assert!(matches!(
+            BinopRow::try_from([256, 0, 0, 0]),
+            Err(TableError::InvalidTag {
+                table: "binop",
+                value: 256
+            })
+        ));
+    }
+
+    #[test]
+    fn binop_table_supported_ops() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let table = BinaryOperationTable::construct(&mut meta, 2);
+        assert!(BinOpTag::iter().all(|op| table.supports(op)));
+
+        let ops = BinOpTag::iter()
+            .filter(|op| *op != BinOpTag::MOD)
+            .collect::<Vec<_>>();
+        let table = table.with_supported_ops(&ops);
+        assert!(!table.supports(BinOpTag::MOD));
+        assert!(table.supports(BinOpTag::DIV));
+    }
+
+    #[test]
+    fn binop_table_too_large() {
+        let rows = try_generate_binop_table(3, 3, MAX_BINOP_ROWS).unwrap();
+        assert_eq!(rows.len() as u128, estimated_binop_rows(3, 3));
+        assert!(try_generate_binop_table(3, 3, rows.len() - 1).is_err());
+
+        assert!(matches!(
+            try_generate_binop_table(12, 12, MAX_BINOP_ROWS),
+            Err(TableError::TableTooLarge { estimated_rows }) if estimated_rows > 1 << 28
+        ));
+        assert_eq!(estimated_binop_rows(64, 64), u128::MAX);
+    }
+
+    #[test]
+    fn binop_table_for_ops() {
+        let ops = [BinOpTag::ADD, BinOpTag::MUL];
+        let rows = generate_binop_table_for(&ops, 3);
+        assert_eq!(rows.len(), 2 * 64);
+        let all = generate_binop_table(3, 3);
+        assert!(rows.iter().all(|row| all.contains(row)));
+    }
+
+    #[test]
+    fn word_width_masks() {
+        assert_eq!(mask(4), 0xF);
+        assert_eq!(mask(8), 0xFF);
+        assert_eq!(mask(0), 0);
+        assert_eq!(mask(64), u64::MAX);
+        assert_eq!(max_value(4), 15);
+        assert_eq!(BinOpTag::SHL.eval(0xF, 1, 4), (mask(4) & 0x1E, false));
+        assert_eq!(
+            generate_unaryop_table(4).len() as u64,
+            4 * (max_value(4) + 1)
+        );
+    }
+
+    #[test]
+    fn encoded_signed_values() {
+        assert_eq!(
+            Encoded::<Fr>::from_signed(-1).inner(),
+            Fr::from(0) - Fr::from(1)
+        );
+        assert_eq!(Encoded::<Fr>::from_signed(5), Encoded::from_unsigned(5));
+        // a two's complement word is only negative as a signed result
+        let minus_one = (-1i64) as u64;
+        assert_eq!(
+            Encoded::<Fr>::from_res(BinOpTag::SSUB, minus_one),
+            Encoded::from_signed(-1)
+        );
+        assert_eq!(
+            Encoded::<Fr>::from_res(BinOpTag::ADD, minus_one).inner(),
+            Fr::from(minus_one)
+        );
+    }
+
+    #[test]
+    fn canonical_binop_table_halves_commutative_ops() {
+        let (add, sub) = (BinOpTag::ADD as u64, BinOpTag::SUB as u64);
+        let rows = canonical_binop_table(generate_binop_table_for(&[BinOpTag::ADD], 4));
+        assert_eq!(rows.len(), 16 * 17 / 2);
+        assert!(rows.contains(&[add, 3, 5, 8]));
+        assert!(!rows.contains(&[add, 5, 3, 8]));
+
+        let rows = canonical_binop_table(generate_binop_table_for(&[BinOpTag::SUB], 4));
+        assert_eq!(rows.len(), 16 * 16);
+        assert!(rows.contains(&[sub, 5, 3, 2]));
+
+        // not ADD, whose tag it truncates to
+        let undefined = [256 + add, 5, 3, 8];
+        assert_eq!(canonical_binop_table(vec![undefined]), vec![undefined]);
+    }
+
+    #[test]
+    fn merge_binop_tables_dedups() {
+        use std::collections::BTreeSet;
+
+        let add = generate_binop_table_for(&[BinOpTag::ADD], 3);
+        let mul = generate_binop_table_for(&[BinOpTag::MUL], 3);
+        let merged = merge_binop_tables(add.clone(), mul.clone());
+        assert_eq!(merged, [add.clone(), mul].concat());
+
+        let twice = merge_binop_tables(merged.clone(), add);
+        assert_eq!(twice, merged);
+        let unique = merged.iter().collect::<BTreeSet<_>>();
+        assert_eq!(unique.len(), merged.len());
+    }
+
+    #[test]
+    fn arith_table_has_no_comparisons() {
+        let rows = generate_arith_table(3);
+        assert!(rows
+            .iter()
+            .all(|row| !BinOpTag::try_from(row[0] as u8).unwrap().is_comparison()));
+        assert!(rows.contains(&[BinOpTag::SLT as u64, 7, 0, 1]));
+        let comparisons = BinOpTag::iter().filter(BinOpTag::is_comparison).count();
+        assert_eq!(
+            rows.len() + comparisons * 64,
+            generate_binop_table(3, 3).len()
+        );
+    }
+
+    #[test]
+    fn comparison_table_semantics() {
+        let rows = generate_comparison_table();
+        assert_eq!(rows.len(), 3 * 5);
+        assert!(rows.contains(&[BinOpTag::LT as u64, 0, 0, 1]));
+        assert!(rows.contains(&[BinOpTag::GT as u64, 1, 0, 1]));
+        assert!(rows.contains(&[BinOpTag::LE as u64, 1, 1, 1]));
+        assert!(rows.contains(&[BinOpTag::GE as u64, 1, 1, 1]));
+        assert!(rows.contains(&[BinOpTag::EQ as u64, 1, 0, 0]));
+    }
+
+    #[test]
+    fn eval_matches_table() {
+        let range = 3;
+        for [tag, lhs, rhs, res] in generate_binop_table(range, range).into_iter().step_by(7) {
+            let op = BinOpTag::try_from(tag as u8).unwrap();
+            assert_eq!(op.eval(lhs, rhs, range), (res, op.is_error(rhs)));
+        }
+        assert_eq!(BinOpTag::ADD.eval(7, 7, range), (6, false));
+        assert_eq!(BinOpTag::SHL.eval(7, 1, range), (6, false));
+        assert_eq!(BinOpTag::DIV.eval(7, 0, range), (0, true));
+        assert_eq!(BinOpTag::SLT.eval(7, 0, range), (1, false));
+    }
+
+    #[test]
+    fn binop_lookup_map() {
+        let map = BinopLookupMap::new(4);
+        let tag = |op: BinOpTag| op as u8;
+        assert_eq!(map.get(tag(BinOpTag::ADD), 15, 15), Some(14));
+        assert_eq!(map.get(tag(BinOpTag::MUL), 3, 5), Some(15));
+        assert_eq!(map.get(tag(BinOpTag::DIV), 7, 0), Some(0));
+        assert_eq!(map.get(tag(BinOpTag::LT), 2, 3), Some(1));
+        assert_eq!(map.get(tag(BinOpTag::SHL), 1, 3), Some(8));
+        // operand outside of the 4-bit domain, and an unknown tag
+        assert_eq!(map.get(tag(BinOpTag::ADD), 16, 0), None);
+        assert_eq!(map.get(0, 1, 1), None);
+
+        assert!(map.contains_row([BinOpTag::ADD as u64, 15, 15, 14]));
+        assert!(!map.contains_row([BinOpTag::ADD as u64, 15, 15, 30]));
+        assert!(!map.contains_row([BinOpTag::ADD as u64, 256, 0, 256]));
+    }
+
+    #[test]
+    fn binop_table_deduped() {
+        let rows = BinaryOperationTable::precompute_deduped(3);
+        assert!(rows.len() <= generate_binop_table(3, 3).len());
+        assert!(rows.windows(2).all(|w| w[0] < w[1]));
+        assert!(rows.binary_search(&[BinOpTag::ADD as u64, 7, 7, 6]).is_ok());
+    }
+
+    #[test]
+    fn tag_display() {
+        assert_eq!(BinOpTag::ADD.to_string(), "+");
+        assert_eq!(BinOpTag::LT.to_string(), "<");
+        assert_eq!(BinOpTag::SHR.to_string(), ">>");
+        assert_eq!(BinOpTag::SLT.to_string(), "slt");
+        assert_eq!(UnaryOpTag::NOT.to_string(), "~");
+        assert_eq!(BlockExitTag::JUMP.to_string(), "jmp");
+        assert_eq!(format!("{}", BlockExitTag::RET), "ret");
+    }
+
+    #[test]
+    fn tag_iteration() {
+        let unary_tags = UnaryOpTag::iter().map(|tag| tag as u64).collect::<Vec<_>>();
+        assert_eq!(unary_tags, vec![1, 2, 3, 4]);
+        assert_eq!(BlockExitTag::iter().count(), 3);
+    }
+
+    #[test]
+    fn unaryop_table_columns() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let table = UnaryOperationTable::construct(&mut meta);
+        assert_eq!(LookupTable::<Fr>::columns(&table).len(), 3);
+        assert_eq!(LookupTable::<Fr>::annotations(&table).len(), 3);
+    }
+
+    struct MismatchedTable(Column<Fixed>);
+
+    impl LookupTable<Fr> for MismatchedTable {
+        fn columns(&self) -> Vec<Column<Any>> {
+            vec![self.0.into()]
+        }
+
+        fn annotations(&self) -> Vec<String> {
+            vec![String::from("a"), String::from("b")]
+        }
+    }
+
+    #[test]
+    fn mismatched_table_shape() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let table = MismatchedTable(meta.fixed_column());
+        assert!(matches!(
+            table.validate_shape(),
+            Err(TableError::ShapeMismatch {
+                columns: 1,
+                annotations: 2
+            })
+        ));
+        let unaryop_table = UnaryOperationTable::construct(&mut meta);
+        assert!(LookupTable::<Fr>::validate_shape(&unaryop_table).is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "1 columns but 2 annotations")]
+    fn mismatched_table_annotate_panics() {
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let table = MismatchedTable(meta.fixed_column());
+        table.annotate_columns(&mut meta);
+    }
+
+    #[test]
+    fn tags_from_mnemonics() {
+        assert_eq!("add".parse::<BinOpTag>().unwrap(), BinOpTag::ADD);
+        assert_eq!("xor".parse::<BinOpTag>().unwrap(), BinOpTag::XOR);
+        assert_eq!("shl".parse::<BinOpTag>().unwrap(), BinOpTag::SHL);
+        assert!(matches!(
+            "foo".parse::<BinOpTag>(),
+            Err(TableError::UnknownMnemonic { table: "binop", .. })
+        ));
+        assert_eq!("+".parse::<BinOpTag>().unwrap(), BinOpTag::ADD);
+
+        assert_eq!("neg".parse::<UnaryOpTag>().unwrap(), UnaryOpTag::NEG);
+        assert_eq!("ret".parse::<BlockExitTag>().unwrap(), BlockExitTag::RET);
+        assert_eq!(
+            "condjump".parse::<BlockExitTag>().unwrap(),
+            BlockExitTag::CONDJUMP
+        );
+    }
+
+    #[test]
+    fn tags_round_trip_through_display() {
+        for tag in BinOpTag::iter() {
+            assert_eq!(tag.to_string().parse::<BinOpTag>().unwrap(), tag);
+            assert_eq!(tag.mnemonic().parse::<BinOpTag>().unwrap(), tag);
+        }
+        for tag in UnaryOpTag::iter() {
+            assert_eq!(tag.to_string().parse::<UnaryOpTag>().unwrap(), tag);
+            assert_eq!(tag.mnemonic().parse::<UnaryOpTag>().unwrap(), tag);
+        }
+        for tag in BlockExitTag::iter() {
+            assert_eq!(tag.to_string().parse::<BlockExitTag>().unwrap(), tag);
+            assert_eq!(tag.mnemonic().parse::<BlockExitTag>().unwrap(), tag);
+        }
+    }
+
+    #[test]
+    fn tags_are_map_keys() {
+        use std::collections::{BTreeSet, HashSet};
+
+        let binop = BinOpTag::iter().collect::<HashSet<_>>();
+        assert_eq!(binop.len(), BinOpTag::iter().count());
+        assert!(binop.contains(&BinOpTag::ADD));
+        let unop = UnaryOpTag::iter().collect::<HashSet<_>>();
+        assert_eq!(unop.len(), UnaryOpTag::iter().count());
+        let exit = BlockExitTag::iter().collect::<HashSet<_>>();
+        assert_eq!(exit.len(), BlockExitTag::iter().count());
+
+        // ordered by discriminant
+        let ordered = BinOpTag::iter().collect::<BTreeSet<_>>();
+        assert!(ordered.into_iter().eq(BinOpTag::iter()));
+    }
+}
@@ -0,0 +1,169 @@
+#[cfg(feature = "serde")]
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::Path,
+};
+
+#[cfg(feature = "digest")]
+use sha2::{Digest, Sha256};
+
+use super::{check_tags, generate_binop_table, BinOpTag, TableError};
+
+/// SHA-256 of `rows`, serialized as their count followed by every value,
+/// each as 8 little-endian bytes.
+///
+/// The fixed columns of a table follow from its rows, so a prover and a
+/// verifier that agree on the digest agree on the table, without either
+/// shipping the rows.
+#[cfg(feature = "digest")]
+pub fn table_digest(rows: &[[u64; 4]]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update((rows.len() as u64).to_le_bytes());
+    for value in rows.iter().flatten() {
+        hasher.update(value.to_le_bytes());
+    }
+    hasher.finalize().into()
+}
+
+/// Rows of the binop table, as produced by `generate_binop_table`.
+///
+/// With the `serde` feature the rows can be saved once and loaded back
+/// instead of being regenerated on every run. Deserializing checks the width
+/// and the tag of every row, so a loaded table is as valid as a generated one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(try_from = "Vec<Vec<u64>>", into = "Vec<Vec<u64>>")
+)]
+pub struct PrecomputedBinopTable {
+    rows: Vec<[u64; 4]>,
+}
+
+impl PrecomputedBinopTable {
+    pub fn generate(range: u32) -> Self {
+        Self {
+            rows: generate_binop_table(range, range),
+        }
+    }
+
+    pub fn rows(&self) -> &[[u64; 4]] {
+        &self.rows
+    }
+
+    /// `table_digest` of the rows.
+    #[cfg(feature = "digest")]
+    pub fn digest(&self) -> [u8; 32] {
+        table_digest(&self.rows)
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn save_to_path(&self, path: impl AsRef<Path>) -> Result<(), TableError> {
+        let writer = BufWriter::new(File::create(path)?);
+        bincode::serialize_into(writer, self)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn load_from_path(path: impl AsRef<Path>) -> Result<Self, TableError> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(bincode::deserialize_from(reader)?)
+    }
+}
+
+impl TryFrom<Vec<Vec<u64>>> for PrecomputedBinopTable {
+    type Error = TableError;
+
+    fn try_from(rows: Vec<Vec<u64>>) -> Result<Self, Self::Error> {
+        let rows = rows
+            .into_iter()
+            .map(|row| {
+                <[u64; 4]>::try_from(row).map_err(|row| TableError::RowTooWide {
+                    table: "binop",
+                    width: row.len(),
+                    columns: 4,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        check_tags::<BinOpTag>("binop", rows.iter().map(|row| row[0]))?;
+        Ok(Self { rows })
+    }
+}
+
+impl From<PrecomputedBinopTable> for Vec<Vec<u64>> {
+    fn from(table: PrecomputedBinopTable) -> Self {
+        table.rows.into_iter().map(Vec::from).collect()
+    }
+}
+
+impl IntoIterator for PrecomputedBinopTable {
+    type Item = [u64; 4];
+    type IntoIter = std::vec::IntoIter<[u64; 4]>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.rows.into_iter()
+    }
+}
+
+impl From<PrecomputedBinopTable> for Vec<[u64; 4]> {
+    fn from(table: PrecomputedBinopTable) -> Self {
+        table.rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn precomputed_table_validates_rows() {
+        let add = BinOpTag::ADD as u64;
+        assert!(PrecomputedBinopTable::try_from(vec![vec![add, 1, 2, 3]]).is_ok());
+        assert!(matches!(
+            PrecomputedBinopTable::try_from(vec![vec![add, 1, 2, 3, 4]]),
+            Err(TableError::RowTooWide { width: 5, .. })
+        ));
+        assert!(matches!(
+            PrecomputedBinopTable::try_from(vec![vec![0, 1, 2, 3]]),
+            Err(TableError::InvalidTag { value: 0, .. })
+        ));
+    }
+
+    #[cfg(feature = "digest")]
+    #[test]
+    fn table_digest_tracks_rows() {
+        let table = PrecomputedBinopTable::generate(2);
+        assert_eq!(table.digest(), PrecomputedBinopTable::generate(2).digest());
+        assert_eq!(table.digest(), table_digest(table.rows()));
+
+        let mut rows = table.rows().to_vec();
+        rows[0][3] += 1;
+        assert_ne!(table_digest(&rows), table.digest());
+        rows[0][3] -= 1;
+        rows.pop();
+        assert_ne!(table_digest(&rows), table.digest());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn precomputed_table_round_trip() {
+        let path = std::env::temp_dir().join(format!("lir-binop-{}.bin", std::process::id()));
+        let table = PrecomputedBinopTable::generate(3);
+        table.save_to_path(&path).unwrap();
+        let loaded = PrecomputedBinopTable::load_from_path(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap(), table);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn precomputed_table_rejects_invalid_file() {
+        let path = std::env::temp_dir().join(format!("lir-binop-bad-{}.bin", std::process::id()));
+        let rows: Vec<Vec<u64>> = vec![vec![0, 1, 2, 3]];
+        std::fs::write(&path, bincode::serialize(&rows).unwrap()).unwrap();
+        let loaded = PrecomputedBinopTable::load_from_path(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(loaded.is_err());
+    }
+}
@@ -1,8 +1,30 @@
 use halo2_proofs::{
-    circuit::Layouter,
+    circuit::{Layouter, Region},
     halo2curves::FieldExt,
-    plonk::{ConstraintSystem, Error, Expression},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector},
 };
+#[cfg(test)]
+use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr, plonk::Circuit};
+
+/// `log::debug!` with the `log` feature, nothing without it. For one line
+/// per table or region; rows are logged with `log_trace!`.
+macro_rules! log_debug {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "log")]
+        log::debug!($($arg)*);
+    };
+}
+pub(crate) use log_debug;
+
+/// `log::trace!` with the `log` feature, nothing without it. For one line
+/// per assigned row.
+macro_rules! log_trace {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "log")]
+        log::trace!($($arg)*);
+    };
+}
+pub(crate) use log_trace;
 
 /// Trait that implements functionality to get a constant expression from
 /// commonly used types.
@@ -42,6 +64,88 @@ pub trait SubCircuitConfig<F: FieldExt> {
     fn new(meta: &mut ConstraintSystem<F>, args: Self::ConfigArgs) -> Self;
 }
 
+/// Advice columns handed out to a `SubCircuitConfig::new` in order, new
+/// columns being created once the pool runs out.
+///
+/// Clones of a pool hand out the same columns, so subcircuits given clones
+/// of one pool share their columns. The floor planner never lays out two
+/// regions on the same rows of a column, so this is sound as long as a
+/// subcircuit only constrains the pooled columns under its own selectors.
+#[derive(Clone, Debug, Default)]
+pub struct ColumnPool {
+    columns: Vec<Column<Advice>>,
+    next: usize,
+}
+
+impl ColumnPool {
+    /// A pool of `num_columns` new columns.
+    pub fn new<F: FieldExt>(meta: &mut ConstraintSystem<F>, num_columns: usize) -> Self {
+        Self {
+            columns: (0..num_columns).map(|_| meta.advice_column()).collect(),
+            next: 0,
+        }
+    }
+
+    /// The next column of the pool, or a new one once all of them have been
+    /// handed out.
+    pub fn advice_column<F: FieldExt>(&mut self, meta: &mut ConstraintSystem<F>) -> Column<Advice> {
+        if self.next == self.columns.len() {
+            self.columns.push(meta.advice_column());
+        }
+        self.next += 1;
+        self.columns[self.next - 1]
+    }
+}
+
+/// Enables `selector` on every row of `offsets` in `region`, for
+/// subcircuits that assign their rows first and turn their gates on once
+/// the whole region is laid out.
+pub fn enable_rows<F: FieldExt>(
+    region: &mut Region<'_, F>,
+    selector: &Selector,
+    offsets: impl IntoIterator<Item = usize>,
+) -> Result<(), Error> {
+    for offset in offsets {
+        selector.enable(region, offset)?;
+    }
+    Ok(())
+}
+
+/// Shape of a `ConstraintSystem` once configured, to sanity check a config
+/// before proving with it.
+///
+/// Counts are taken before keygen, so selectors are not yet turned into
+/// fixed columns. `degree` is the maximum degree of the gates and lookups;
+/// the extended domain grows with it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConfigStats {
+    pub num_advice_columns: usize,
+    pub num_fixed_columns: usize,
+    pub num_instance_columns: usize,
+    pub num_selectors: usize,
+    pub num_gates: usize,
+    pub num_lookups: usize,
+    pub degree: usize,
+}
+
+impl ConfigStats {
+    pub fn of<F: FieldExt>(meta: &ConstraintSystem<F>) -> Self {
+        Self {
+            num_advice_columns: meta.num_advice_columns(),
+            num_fixed_columns: meta.num_fixed_columns(),
+            num_instance_columns: meta.num_instance_columns(),
+            num_selectors: meta.num_selectors(),
+            num_gates: meta.gates().len(),
+            num_lookups: meta.lookups().len(),
+            degree: meta.degree(),
+        }
+    }
+}
+
+/// Rows reserved at the end of the circuit on top of `min_num_rows`, for
+/// the blinding factors. More than halo2 needs for the gates used here.
+pub const BLINDING_ROWS: usize = 16;
+
 pub trait SubCircuit<F: FieldExt> {
     type Config: SubCircuitConfig<F>;
 
@@ -49,9 +153,56 @@ pub trait SubCircuit<F: FieldExt> {
         vec![]
     }
 
+    /// Number of rows the subcircuit needs, so that a driver can choose `k`
+    /// as `ceil(log2(min_num_rows))` plus room for the blinding rows.
+    fn min_num_rows(&self) -> usize {
+        0
+    }
+
     fn synthesize_sub(
         &self,
         config: &Self::Config,
         layouter: &mut impl Layouter<F>,
     ) -> Result<(), Error>;
 }
+
+/// Object-safe counterpart of `SubCircuit`, implemented by a subcircuit
+/// paired with its config, so that subcircuits of different types can be
+/// collected into one `Vec<Box<dyn DynSubCircuit<F, L>>>` and synthesized in
+/// turn.
+///
+/// `Layouter` has generic methods and cannot be made into an object itself,
+/// hence the layouter type is a parameter of the trait.
+pub trait DynSubCircuit<F: FieldExt, L: Layouter<F>> {
+    fn synthesize_sub(&self, layouter: &mut L) -> Result<(), Error>;
+}
+
+impl<F: FieldExt, L: Layouter<F>, C: SubCircuit<F>> DynSubCircuit<F, L> for (&C, &C::Config) {
+    fn synthesize_sub(&self, layouter: &mut L) -> Result<(), Error> {
+        let (circuit, config) = *self;
+        circuit.synthesize_sub(config, layouter)
+    }
+}
+
+/// Runs `circuit` at `2^k` rows on the `MockProver`, with its own instance,
+/// and panics with the failures unless every constraint is satisfied.
+#[cfg(test)]
+pub fn assert_satisfied<C>(circuit: &C, k: u32)
+where
+    C: Circuit<Fr> + SubCircuit<Fr>,
+{
+    let prover = MockProver::run(k, circuit, circuit.instance()).unwrap();
+    prover.assert_satisfied();
+}
+
+/// Same as `assert_satisfied`, but panics unless some constraint fails.
+/// A circuit that fails to synthesize panics as well, as it proves nothing
+/// about its constraints.
+#[cfg(test)]
+pub fn assert_fails<C>(circuit: &C, k: u32)
+where
+    C: Circuit<Fr> + SubCircuit<Fr>,
+{
+    let prover = MockProver::run(k, circuit, circuit.instance()).unwrap();
+    assert!(prover.verify().is_err(), "the constraints are satisfied");
+}
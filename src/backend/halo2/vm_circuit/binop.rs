@@ -1,48 +1,2235 @@
-use std::marker::PhantomData;
+use std::{
+    fmt,
+    marker::PhantomData,
+    sync::{Arc, OnceLock},
+};
 
+use super::canonical_binop_table;
+use super::gadgets::{IsEqualGadget, IsZeroGadget};
+use super::generate_arith_table;
+use super::generate_binop_table_for;
+use super::generate_comparison_table;
+use super::max_value;
+use super::padded_len;
+use super::to_field;
+use super::BinOpTag;
+use super::BinOpTagTable;
 use super::BinaryOperationTable;
+use super::ComparisonTable;
+use super::Encoded;
+use super::LookupTable;
+use super::RangeCheckTable;
 use super::RegisterTable;
-use super::SubCircuit;
+use super::TableError;
+use super::NUM_REGISTERS;
+use super::{SubCircuit, SubCircuitConfig};
+use crate::backend::halo2::utils::{
+    enable_rows, log_trace, ColumnPool, ConfigStats, Expr, BLINDING_ROWS,
+};
 use halo2_proofs::{
-    circuit::Layouter,
+    circuit::{AssignedCell, FloorPlanner, Layouter, Region, SimpleFloorPlanner, Value},
+    dev::{FailureLocation, MockProver, VerifyFailure},
     halo2curves::FieldExt,
-    plonk::{Advice, Column, Error},
+    plonk::{
+        Advice, Circuit, Column, ConstraintSystem, Error, Expression, Fixed, Instance, Selector,
+    },
+    poly::Rotation,
 };
+use strum::IntoEnumIterator;
 
-fn generate_binop_table() -> Vec<[u8; 4]> {
-    let mut all_cases = vec![];
-    // TODO: for all cases
-    all_cases.push([1, 1, 1, 2]);
-    all_cases
-}
+/// Operand width of the table loaded by `BinOpCircuit`.
+const BIT_WIDTH: u32 = 4;
 
+/// Name of the lookup of an operation into the binop table, which
+/// `explain_lookup_failure` reports on.
+const BINOP_LOOKUP: &str = "binop_lookup";
+
+/// Binops looked up in a precomputed table.
+///
+/// Comparison ops are not in `binop_table`: their rows set `is_cmp` and are
+/// looked up by operand order in `comparison_table`, the order being
+/// witnessed by the decomposition `lhs - rhs + 2^w = ge * 2^w + low` with
+/// `lhs`, `rhs` and `low` range checked to `w = bit_width` bits. A row
+/// cannot pick the wrong table, since neither holds the tags of the other.
+///
+/// The table maps a shift by `w` or more to 0, but such a shift is rejected
+/// here: on `SHL` and `SHR` rows `w - 1 - rhs` is range checked as well.
+///
+/// The operands of a commutative op may be swapped before the binop
+/// lookup, the tag table allowing the swap on these ops only, so that the
+/// table only needs their rows with `lhs <= rhs`, see
+/// `with_canonical_operands`.
+#[derive(Clone, Debug)]
 pub struct BinOpConfig<F: FieldExt> {
     binop_table: BinaryOperationTable,
     register_table: RegisterTable,
+    comparison_table: ComparisonTable,
+    range_table: RangeCheckTable,
+    /// Rejects an operation row whose tag is not a `BinOpTag`, whichever
+    /// table its other columns are looked up in.
+    tag_table: BinOpTagTable,
+    tag_column: Column<Advice>,
+    lhs_column: Column<Advice>,
+    rhs_column: Column<Advice>,
+    res_column: Column<Advice>,
+    is_error_column: Column<Advice>,
+    /// Whether the op carries out of the `bit_width`-bit word, see
+    /// `BinOpTag::carry`. Looked up along with the row, and 0 on comparison
+    /// rows.
+    carry_column: Column<Advice>,
+    /// 1 on comparison rows, see `BinOpTag::is_comparison`.
+    is_cmp_column: Column<Advice>,
+    /// Whether `lhs >= rhs`, on comparison rows.
+    cmp_ge_column: Column<Advice>,
+    /// Low `bit_width` bits of `lhs - rhs + 2^bit_width`, on comparison rows.
+    cmp_low_column: Column<Advice>,
+    cmp_eq: IsEqualGadget<F>,
+    /// 1 if the binop lookup reads `rhs, lhs` rather than `lhs, rhs`, only
+    /// allowed on commutative ops.
+    swap_column: Column<Advice>,
+    /// `lhs` and `rhs`, in the order the binop lookup reads them.
+    canonical_lhs_column: Column<Advice>,
+    canonical_rhs_column: Column<Advice>,
+    /// Whether `tag` is each of the comparison tags. Unlike `is_cmp`, which
+    /// is only tied to the tag by the lookups, these are computed from the
+    /// tag, and force a comparison `res` to be boolean in a gate of its own.
+    cmp_tags: Vec<(BinOpTag, IsEqualGadget<F>)>,
+    /// Enabled on every operation row. Every lookup input is multiplied by
+    /// it, so rows outside of the operations are not looked up at all.
+    q_enable: Selector,
+    /// Whether `(tag - SHL) * (tag - SHR)` is zero, i.e. the op is a shift.
+    is_shift: IsZeroGadget<F>,
+    /// Enabled on rows whose `res` must equal `expected_res_column`.
+    q_binop: Selector,
+    expected_res_column: Column<Advice>,
+    /// Enabled on rows whose operands are read from `register_table`.
+    q_registers: Selector,
+    lhs_index_column: Column<Advice>,
+    rhs_index_column: Column<Advice>,
+    /// Enabled on rows whose `res` is written to the register in
+    /// `res_index_column`. The register table holds one value per register,
+    /// so a register is written at most once, and later reads of it see
+    /// the written `res`.
+    q_write: Selector,
+    res_index_column: Column<Advice>,
+    /// Enabled on rows whose `rhs` is the immediate in `immediate_column`.
+    /// The immediate is fixed, so it is part of the circuit rather than of
+    /// the witness.
+    q_immediate: Selector,
+    immediate_column: Column<Fixed>,
+    /// Public results, row `i` holding the `res` of the `i`-th operation.
+    instance: Column<Instance>,
+    bit_width: u32,
+    /// Ops the operation rows may witness, see `with_ops`.
+    ops: Vec<BinOpTag>,
+    /// Rows an operation region may use, see `with_capacity`.
+    capacity: usize,
+    /// Whether the tables are left empty, see `disabled`.
+    disabled: bool,
+    /// Whether the binop table only holds the canonical rows, see
+    /// `with_canonical_operands`.
+    canonical: bool,
+    /// Rows of `binop_table`, generated on the first load and shared by the
+    /// clones of the config, since halo2 may synthesize several times.
+    precomputed: Arc<OnceLock<Vec<[u64; 4]>>>,
     _marker: PhantomData<F>,
 }
 
+/// The operand width of the config is the one of `binop_table`, at most 8
+/// since rows hold bytes.
+///
+/// The operation columns are taken from `pool`, except `is_cmp`, which
+/// enables the comparison gates without a selector and must stay 0 on the
+/// rows of other subcircuits.
+pub struct BinOpConfigArgs {
+    pub binop_table: BinaryOperationTable,
+    pub register_table: RegisterTable,
+    pub pool: ColumnPool,
+}
+
+impl<F: FieldExt> SubCircuitConfig<F> for BinOpConfig<F> {
+    type ConfigArgs = BinOpConfigArgs;
+
+    fn new(
+        meta: &mut ConstraintSystem<F>,
+        BinOpConfigArgs {
+            binop_table,
+            register_table,
+            mut pool,
+        }: Self::ConfigArgs,
+    ) -> Self {
+        let bit_width = binop_table.bits;
+        assert!(bit_width <= 8, "binop table operands must fit in a byte");
+
+        let tag_column = pool.advice_column(meta);
+        let lhs_column = pool.advice_column(meta);
+        let rhs_column = pool.advice_column(meta);
+        let res_column = pool.advice_column(meta);
+        let is_error_column = pool.advice_column(meta);
+        let carry_column = pool.advice_column(meta);
+        let is_cmp_column = meta.advice_column();
+        let cmp_ge_column = pool.advice_column(meta);
+        let cmp_low_column = pool.advice_column(meta);
+        let swap_column = pool.advice_column(meta);
+        let canonical_lhs_column = pool.advice_column(meta);
+        let canonical_rhs_column = pool.advice_column(meta);
+        let q_enable = meta.complex_selector();
+        let q_binop = meta.selector();
+        let expected_res_column = pool.advice_column(meta);
+        let q_registers = meta.complex_selector();
+        let lhs_index_column = pool.advice_column(meta);
+        let rhs_index_column = pool.advice_column(meta);
+        let q_write = meta.complex_selector();
+        let res_index_column = pool.advice_column(meta);
+        let q_immediate = meta.selector();
+        let immediate_column = meta.fixed_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(res_column);
+        meta.enable_equality(instance);
+
+        let comparison_table = ComparisonTable::construct(meta);
+        let range_table = RangeCheckTable::construct(meta, bit_width);
+        let tag_table = BinOpTagTable::construct(meta);
+
+        binop_table.annotate_columns(meta);
+        register_table.annotate_columns(meta);
+        comparison_table.annotate_columns(meta);
+        range_table.annotate_columns(meta);
+        tag_table.annotate_columns(meta);
+
+        tag_table.lookup_tag(
+            meta,
+            "binop_tag_lookup",
+            |meta| meta.query_selector(q_enable),
+            |meta| meta.query_advice(tag_column, Rotation::cur()),
+            |meta| meta.query_advice(swap_column, Rotation::cur()),
+        );
+
+        meta.create_gate("binop canonical operands", |meta| {
+            let q_enable = meta.query_selector(q_enable);
+            let swap = meta.query_advice(swap_column, Rotation::cur());
+            let lhs = meta.query_advice(lhs_column, Rotation::cur());
+            let rhs = meta.query_advice(rhs_column, Rotation::cur());
+            let canonical_lhs = meta.query_advice(canonical_lhs_column, Rotation::cur());
+            let canonical_rhs = meta.query_advice(canonical_rhs_column, Rotation::cur());
+            vec![
+                q_enable.clone()
+                    * (canonical_lhs.clone() - lhs.clone() - swap * (rhs.clone() - lhs.clone())),
+                q_enable * (canonical_lhs + canonical_rhs - lhs - rhs),
+            ]
+        });
+
+        meta.lookup_any(BINOP_LOOKUP, |meta| {
+            let one = Expression::Constant(F::one());
+            let q_enable = meta.query_selector(q_enable);
+            let is_cmp = meta.query_advice(is_cmp_column, Rotation::cur());
+            let is_arith = q_enable * (one - is_cmp);
+            let advice = [
+                tag_column,
+                canonical_lhs_column,
+                canonical_rhs_column,
+                res_column,
+                is_error_column,
+                carry_column,
+            ];
+            binop_table.lookup_tuples_gated(meta, is_arith, &advice)
+        });
+
+        let cmp_eq = IsEqualGadget::configure(
+            meta,
+            |meta| meta.query_advice(is_cmp_column, Rotation::cur()),
+            |meta| meta.query_advice(lhs_column, Rotation::cur()),
+            |meta| meta.query_advice(rhs_column, Rotation::cur()),
+        );
+
+        let cmp_tags = BinOpTag::iter()
+            .filter(BinOpTag::is_comparison)
+            .map(|op| {
+                let gadget = IsEqualGadget::configure(
+                    meta,
+                    |meta| meta.query_selector(q_enable),
+                    |meta| meta.query_advice(tag_column, Rotation::cur()),
+                    |_| op.expr(),
+                );
+                (op, gadget)
+            })
+            .collect::<Vec<_>>();
+
+        meta.create_gate("binop comparison res is boolean", |meta| {
+            let one = Expression::Constant(F::one());
+            let q_enable = meta.query_selector(q_enable);
+            let res = meta.query_advice(res_column, Rotation::cur());
+            // The tags are distinct, so at most one term is 1.
+            let is_cmp_tag = cmp_tags
+                .iter()
+                .fold(Expression::Constant(F::zero()), |acc, (_, gadget)| {
+                    acc + gadget.expr()
+                });
+            vec![q_enable * is_cmp_tag * res.clone() * (one - res)]
+        });
+
+        meta.create_gate("binop comparison order", |meta| {
+            let one = Expression::Constant(F::one());
+            let shift = Expression::Constant(F::from(1u64 << bit_width));
+            let is_cmp = meta.query_advice(is_cmp_column, Rotation::cur());
+            let lhs = meta.query_advice(lhs_column, Rotation::cur());
+            let rhs = meta.query_advice(rhs_column, Rotation::cur());
+            let is_error = meta.query_advice(is_error_column, Rotation::cur());
+            let carry = meta.query_advice(carry_column, Rotation::cur());
+            let ge = meta.query_advice(cmp_ge_column, Rotation::cur());
+            let low = meta.query_advice(cmp_low_column, Rotation::cur());
+
+            vec![
+                is_cmp.clone() * (one.clone() - is_cmp.clone()),
+                is_cmp.clone() * ge.clone() * (one - ge.clone()),
+                is_cmp.clone() * (lhs - rhs + shift.clone() - ge * shift - low),
+                is_cmp.clone() * is_error,
+                is_cmp * carry,
+            ]
+        });
+
+        for (name, column) in [
+            ("binop_cmp_lhs_range", lhs_column),
+            ("binop_cmp_rhs_range", rhs_column),
+            ("binop_cmp_low_range", cmp_low_column),
+        ] {
+            range_table.range_check(meta, name, |meta| {
+                let q_enable = meta.query_selector(q_enable);
+                let is_cmp = meta.query_advice(is_cmp_column, Rotation::cur());
+                q_enable * is_cmp * meta.query_advice(column, Rotation::cur())
+            });
+        }
+
+        let is_shift = IsZeroGadget::configure(
+            meta,
+            |meta| meta.query_selector(q_enable),
+            |meta| {
+                let tag = meta.query_advice(tag_column, Rotation::cur());
+                let shl: Expression<F> = BinOpTag::SHL.expr();
+                let shr: Expression<F> = BinOpTag::SHR.expr();
+                (tag.clone() - shl) * (tag - shr)
+            },
+        );
+
+        range_table.range_check(meta, "binop_shift_amount_range", |meta| {
+            let q_enable = meta.query_selector(q_enable);
+            let max_shift = Expression::Constant(F::from(bit_width as u64 - 1));
+            let rhs = meta.query_advice(rhs_column, Rotation::cur());
+            q_enable * is_shift.expr() * (max_shift - rhs)
+        });
+
+        meta.lookup_any("binop_comparison_lookup", |meta| {
+            let q_enable = meta.query_selector(q_enable);
+            let is_cmp = q_enable * meta.query_advice(is_cmp_column, Rotation::cur());
+            let inputs = [
+                meta.query_advice(tag_column, Rotation::cur()),
+                meta.query_advice(cmp_ge_column, Rotation::cur()),
+                cmp_eq.expr(),
+                meta.query_advice(res_column, Rotation::cur()),
+            ];
+            inputs
+                .into_iter()
+                .map(|input| is_cmp.clone() * input)
+                .zip(comparison_table.table_exprs(meta))
+                .collect()
+        });
+
+        meta.create_gate("binop res matches expected", |meta| {
+            let q_binop = meta.query_selector(q_binop);
+            let res_value = meta.query_advice(res_column, Rotation::cur());
+            let expected_res = meta.query_advice(expected_res_column, Rotation::cur());
+            vec![q_binop * (res_value - expected_res)]
+        });
+
+        meta.create_gate("binop rhs immediate", |meta| {
+            let q_immediate = meta.query_selector(q_immediate);
+            let rhs = meta.query_advice(rhs_column, Rotation::cur());
+            let immediate = meta.query_fixed(immediate_column, Rotation::cur());
+            vec![q_immediate * (rhs - immediate)]
+        });
+
+        // The reads and the write are matched against the enabled rows of
+        // the register table, see `RegisterTable::read_tuples`: a read or
+        // write of `(0, 0)` would otherwise be satisfied by any unassigned
+        // row, whatever register 0 holds.
+        for (name, selector, index_column, operand_column) in [
+            (
+                "binop_lhs_register_lookup",
+                q_registers,
+                lhs_index_column,
+                lhs_column,
+            ),
+            (
+                "binop_rhs_register_lookup",
+                q_registers,
+                rhs_index_column,
+                rhs_column,
+            ),
+            (
+                "binop_res_register_lookup",
+                q_write,
+                res_index_column,
+                res_column,
+            ),
+        ] {
+            meta.lookup_any(name, |meta| {
+                let q_registers = meta.query_selector(selector);
+                let index_value = meta.query_advice(index_column, Rotation::cur());
+                let operand_value = meta.query_advice(operand_column, Rotation::cur());
+                register_table.read_tuples(meta, q_registers, index_value, operand_value)
+            });
+        }
+
+        Self {
+            binop_table,
+            register_table,
+            comparison_table,
+            range_table,
+            tag_table,
+            tag_column,
+            lhs_column,
+            rhs_column,
+            res_column,
+            is_error_column,
+            carry_column,
+            is_cmp_column,
+            cmp_ge_column,
+            cmp_low_column,
+            cmp_eq,
+            swap_column,
+            canonical_lhs_column,
+            canonical_rhs_column,
+            cmp_tags,
+            q_enable,
+            is_shift,
+            q_binop,
+            expected_res_column,
+            q_registers,
+            lhs_index_column,
+            rhs_index_column,
+            q_write,
+            res_index_column,
+            q_immediate,
+            immediate_column,
+            instance,
+            bit_width,
+            ops: BinOpTag::iter().collect(),
+            capacity: usize::MAX,
+            disabled: false,
+            canonical: false,
+            precomputed: Arc::default(),
+            _marker: PhantomData::default(),
+        }
+    }
+}
+
 impl<F: FieldExt> BinOpConfig<F> {
+    /// Configures `BinOpCircuit` in `meta` and returns the resulting shape,
+    /// see `ConfigStats`.
+    pub fn describe(meta: &mut ConstraintSystem<F>) -> ConfigStats {
+        BinOpCircuit::<F>::configure(meta);
+        ConfigStats::of(meta)
+    }
+
     pub fn load_binop_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
-        let precomputed_binop = generate_binop_table();
+        if self.disabled {
+            // The lookup inputs all vanish off the `q_enable` rows, so the
+            // unassigned rows of the other tables match them.
+            self.binop_table.load(layouter, std::iter::empty())?;
+            return Ok(());
+        }
+        let precomputed_binop = self.precomputed_binop().iter().copied();
         self.binop_table.load(layouter, precomputed_binop)?;
+        self.comparison_table.load(layouter)?;
+        self.range_table.load(layouter)?;
+        self.tag_table.load(layouter)?;
+        Ok(())
+    }
+
+    /// Rows of the largest table `load_binop_table` loads, padding included.
+    pub fn table_rows(&self) -> usize {
+        if self.disabled {
+            return padded_len(0);
+        }
+        let binop = padded_len(self.precomputed_binop().len());
+        let comparison = generate_comparison_table().len();
+        let range = 1 << self.bit_width;
+        let tags = BinOpTagTable::num_rows();
+        binop.max(comparison).max(range).max(tags)
+    }
+
+    /// Rows of the binop table at `bit_width`, restricted to the allowed
+    /// arithmetic ops and generated at most once.
+    pub fn precomputed_binop(&self) -> &[[u64; 4]] {
+        let ops = self
+            .ops
+            .iter()
+            .copied()
+            .filter(|op| !op.is_comparison())
+            .collect::<Vec<_>>();
+        self.precomputed_binop_with(|range| {
+            let rows = generate_binop_table_for(&ops, range);
+            if self.canonical {
+                canonical_binop_table(rows)
+            } else {
+                rows
+            }
+        })
+    }
+
+    fn precomputed_binop_with(&self, generate: impl FnOnce(u32) -> Vec<[u64; 4]>) -> &[[u64; 4]] {
+        self.precomputed.get_or_init(|| generate(self.bit_width))
+    }
+
+    /// Bounds the offsets `load_binop_row_checked` accepts to
+    /// `0..capacity`, typically the usable rows of the circuit at its `k`.
+    /// Unbounded by default.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Restricts the operations to `ops`, so that only their rows are
+    /// loaded in the binop table. All ops are allowed by default.
+    ///
+    /// An arithmetic op outside of `ops` fails the binop lookup, and
+    /// `load_binop_row_checked` rejects any op outside of `ops` before
+    /// assigning it, comparisons included.
+    pub fn with_ops(mut self, ops: &[BinOpTag]) -> Self {
+        self.ops = ops.to_vec();
+        let arith_ops = ops
+            .iter()
+            .copied()
+            .filter(|op| !op.is_comparison())
+            .collect::<Vec<_>>();
+        self.binop_table = self.binop_table.with_supported_ops(&arith_ops);
+        // the rows depend on the ops, so they cannot be shared with clones
+        // taken before
+        self.precomputed = Arc::default();
+        self
+    }
+
+    /// Loads only the rows with `lhs <= rhs` of the commutative ops, see
+    /// `canonical_binop_table`. The operands of such an op are swapped when
+    /// `lhs > rhs` whether or not the table is canonical, so the witness is
+    /// the same either way.
+    pub fn with_canonical_operands(mut self) -> Self {
+        self.canonical = true;
+        self.precomputed = Arc::default();
+        self
+    }
+
+    /// Turns the subcircuit off, for circuits that witness no binop: no op
+    /// is allowed, and `load_binop_table` only assigns the padding row of
+    /// the binop table.
+    ///
+    /// The columns, gates and lookups are the same as when enabled, since
+    /// they are created by `new`, but with `q_enable` never set none of them
+    /// constrains anything.
+    pub fn disabled(self) -> Self {
+        let mut config = self.with_ops(&[]);
+        config.disabled = true;
+        config
+    }
+
+    pub fn load_register_table(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        values: Vec<Value<F>>,
+    ) -> Result<(), Error> {
+        self.register_table.load(layouter, values)?;
+        Ok(())
+    }
+
+    /// Assigns one operation at `offset`. `is_error` is 1 when the operation
+    /// traps, see `BinOpTag::is_error`, and `carry` when it carries out of
+    /// the word, see `BinOpTag::carry`. When `expected_res` is given, the
+    /// `q_binop` gate additionally forces `res` to equal it. When
+    /// `registers` holds `(lhs_index, rhs_index)`, `lhs` and `rhs` must be the
+    /// values of those registers.
+    ///
+    /// Returns the `res` cell, so that it can be bound to the public result.
+    #[allow(clippy::too_many_arguments)]
+    pub fn load_binop_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        op: Value<F>,
+        lhs: Value<F>,
+        rhs: Value<F>,
+        res: Value<F>,
+        is_error: Value<F>,
+        carry: Value<F>,
+        expected_res: Option<Value<F>>,
+        registers: Option<(Value<F>, Value<F>)>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let res_cell = self.assign_binop_row(
+            region,
+            offset,
+            op,
+            lhs,
+            rhs,
+            res,
+            is_error,
+            carry,
+            expected_res,
+            registers,
+        )?;
+        self.q_enable.enable(region, offset)?;
+        Ok(res_cell)
+    }
+
+    /// Same as `load_binop_row`, but leaves `q_enable` off, so that a
+    /// region of several operations can enable it with `enable_rows`.
+    #[allow(clippy::too_many_arguments)]
+    fn assign_binop_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        op: Value<F>,
+        lhs: Value<F>,
+        rhs: Value<F>,
+        res: Value<F>,
+        is_error: Value<F>,
+        carry: Value<F>,
+        expected_res: Option<Value<F>>,
+        registers: Option<(Value<F>, Value<F>)>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        log_trace!("binop row {offset}: {op:?} {lhs:?} {rhs:?} = {res:?}");
+        region.assign_advice(|| "tag", self.tag_column, offset, || op)?;
+        region.assign_advice(|| "lhs", self.lhs_column, offset, || lhs)?;
+        region.assign_advice(|| "rhs", self.rhs_column, offset, || rhs)?;
+        let res_cell = region.assign_advice(|| "res", self.res_column, offset, || res)?;
+        region.assign_advice(|| "is_error", self.is_error_column, offset, || is_error)?;
+        region.assign_advice(|| "carry", self.carry_column, offset, || carry)?;
+        let [is_cmp, ge, low] = self.comparison_witness(op, lhs, rhs);
+        region.assign_advice(|| "is cmp", self.is_cmp_column, offset, || is_cmp)?;
+        region.assign_advice(|| "cmp ge", self.cmp_ge_column, offset, || ge)?;
+        region.assign_advice(|| "cmp low", self.cmp_low_column, offset, || low)?;
+        let [swap, canonical_lhs, canonical_rhs] = self.canonical_witness(op, lhs, rhs);
+        region.assign_advice(|| "swap", self.swap_column, offset, || swap)?;
+        region.assign_advice(
+            || "canonical lhs",
+            self.canonical_lhs_column,
+            offset,
+            || canonical_lhs,
+        )?;
+        region.assign_advice(
+            || "canonical rhs",
+            self.canonical_rhs_column,
+            offset,
+            || canonical_rhs,
+        )?;
+        self.cmp_eq.assign(region, offset, lhs, rhs)?;
+        for (tag, gadget) in &self.cmp_tags {
+            gadget.assign(region, offset, op, Value::known(F::from(*tag as u64)))?;
+        }
+        let shl = F::from(BinOpTag::SHL as u64);
+        let shr = F::from(BinOpTag::SHR as u64);
+        let shift_value = op.map(|op| (op - shl) * (op - shr));
+        self.is_shift.assign(region, offset, shift_value)?;
+        if let Some(expected_res) = expected_res {
+            self.q_binop.enable(region, offset)?;
+            region.assign_advice(
+                || "expected res",
+                self.expected_res_column,
+                offset,
+                || expected_res,
+            )?;
+        }
+        if let Some((lhs_index, rhs_index)) = registers {
+            self.q_registers.enable(region, offset)?;
+            region.assign_advice(|| "lhs index", self.lhs_index_column, offset, || lhs_index)?;
+            region.assign_advice(|| "rhs index", self.rhs_index_column, offset, || rhs_index)?;
+        }
+        Ok(res_cell)
+    }
+
+    /// Writes `res` of the operation at `offset` to the register
+    /// `res_index`, i.e. requires the register to hold `res`.
+    pub fn assign_res_register(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        res_index: Value<F>,
+    ) -> Result<(), Error> {
+        self.q_write.enable(region, offset)?;
+        region.assign_advice(|| "res index", self.res_index_column, offset, || res_index)?;
+        Ok(())
+    }
+
+    /// Binds `rhs` of the operation at `offset` to the constant `immediate`,
+    /// for instructions whose right operand is a literal rather than a
+    /// register read.
+    pub fn assign_immediate_rhs(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        immediate: F,
+    ) -> Result<(), Error> {
+        self.q_immediate.enable(region, offset)?;
+        region.assign_fixed(
+            || "rhs immediate",
+            self.immediate_column,
+            offset,
+            || Value::known(immediate),
+        )?;
         Ok(())
     }
+
+    /// Same as `load_binop_row`, but reports an `offset` past the capacity
+    /// of the config, or an `op` it does not allow, before assigning
+    /// anything.
+    #[allow(clippy::too_many_arguments)]
+    pub fn load_binop_row_checked(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        op: Value<F>,
+        lhs: Value<F>,
+        rhs: Value<F>,
+        res: Value<F>,
+        is_error: Value<F>,
+        carry: Value<F>,
+        expected_res: Option<Value<F>>,
+        registers: Option<(Value<F>, Value<F>)>,
+    ) -> Result<AssignedCell<F, F>, TableError> {
+        self.check_binop_row(offset, op)?;
+        let res_cell = self.load_binop_row(
+            region,
+            offset,
+            op,
+            lhs,
+            rhs,
+            res,
+            is_error,
+            carry,
+            expected_res,
+            registers,
+        )?;
+        Ok(res_cell)
+    }
+
+    /// The checks of `load_binop_row_checked`.
+    fn check_binop_row(&self, offset: usize, op: Value<F>) -> Result<(), TableError> {
+        if offset >= self.capacity {
+            return Err(TableError::OffsetOutOfRange {
+                offset,
+                capacity: self.capacity,
+            });
+        }
+        let mut rejected = None;
+        op.map(|op| {
+            let tag = op.get_lower_128();
+            let allowed = self.ops.iter().any(|allowed| *allowed as u128 == tag);
+            if !allowed {
+                rejected = Some(tag as u64);
+            }
+        });
+        match rejected {
+            Some(tag) => Err(TableError::OpNotAllowed { tag }),
+            None => Ok(()),
+        }
+    }
+
+    /// `[swap, canonical_lhs, canonical_rhs]` of a row: the operands of a
+    /// commutative op are swapped when `lhs > rhs`, the others never.
+    fn canonical_witness(&self, op: Value<F>, lhs: Value<F>, rhs: Value<F>) -> [Value<F>; 3] {
+        let witness = op.zip(lhs).zip(rhs).map(|((op, lhs), rhs)| {
+            let tag = decode_tag(op);
+            let commutative = matches!(tag, Some(tag) if tag.is_commutative());
+            if commutative && lhs.get_lower_128() > rhs.get_lower_128() {
+                [F::one(), rhs, lhs]
+            } else {
+                [F::zero(), lhs, rhs]
+            }
+        });
+        [0, 1, 2].map(|i| witness.map(|witness| witness[i]))
+    }
+
+    /// `[is_cmp, ge, low]` of a row, all 0 unless `op` is a comparison.
+    fn comparison_witness(&self, op: Value<F>, lhs: Value<F>, rhs: Value<F>) -> [Value<F>; 3] {
+        let shift = F::from(1u64 << self.bit_width);
+        let witness = op.zip(lhs).zip(rhs).map(|((op, lhs), rhs)| {
+            let tag = decode_tag(op);
+            if !matches!(tag, Some(tag) if tag.is_comparison()) {
+                return [F::zero(); 3];
+            }
+            let ge = if lhs.get_lower_128() >= rhs.get_lower_128() {
+                F::one()
+            } else {
+                F::zero()
+            };
+            [F::one(), ge, lhs - rhs + shift - ge * shift]
+        });
+        [0, 1, 2].map(|i| witness.map(|witness| witness[i]))
+    }
+
+    /// `carry` of a row at `bit_width`, see `BinOpTag::carry`. 0 for an
+    /// undefined tag or operands wider than the word, whose row cannot match
+    /// the table anyway.
+    fn carry_witness(&self, op: Value<F>, lhs: Value<F>, rhs: Value<F>) -> Value<F> {
+        op.zip(lhs).zip(rhs).map(|((op, lhs), rhs)| {
+            let tag = decode_tag(op);
+            let [lhs, rhs] = [lhs, rhs].map(|value| value.get_lower_128());
+            let bound = max_value(self.bit_width) as u128;
+            let fits = lhs <= bound && rhs <= bound;
+            let (lhs, rhs) = (lhs as u64, rhs as u64);
+            let carry = fits && matches!(tag, Some(tag) if tag.carry(lhs, rhs, self.bit_width));
+            F::from(carry as u64)
+        })
+    }
 }
 
-pub struct BinOpCircuit<F: FieldExt> {
-    _marker: PhantomData<F>,
+/// Witnesses one `(tag, lhs, rhs, res, is_error)` operation per row. The
+/// `carry` of a row is derived at synthesis time, at the width of the
+/// config the circuit is synthesized with.
+///
+/// The regions are laid out by `P`. `SimpleFloorPlanner` stacks them one
+/// after the other; `floor_planner::V1` packs them into fewer rows, which
+/// pays off once the circuit holds several large regions. The constraints
+/// are the same either way.
+pub struct BinOpCircuit<F: FieldExt, P: FloorPlanner = SimpleFloorPlanner> {
+    ops: Vec<[Value<F>; 5]>,
+    expected_res: Vec<Option<Value<F>>>,
+    /// `(lhs_index, rhs_index)` registers the operands are read from.
+    operand_registers: Vec<Option<(Value<F>, Value<F>)>>,
+    /// Register the result is written to.
+    res_registers: Vec<Option<Value<F>>>,
+    /// Register values, by index, loaded when the circuit runs on its own.
+    registers: Vec<Value<F>>,
+    /// Constant the `rhs` of an operation is bound to, if any.
+    immediate_rhs: Vec<Option<F>>,
+    /// Encoded result of every operation, exposed as the instance column.
+    public_res: Vec<F>,
+    _marker: PhantomData<P>,
 }
 
-impl<F: FieldExt> SubCircuit<F> for BinOpCircuit<F> {
+// Not derived, as the floor planners implement neither `Clone` nor `Debug`.
+impl<F: FieldExt, P: FloorPlanner> Clone for BinOpCircuit<F, P> {
+    fn clone(&self) -> Self {
+        Self {
+            ops: self.ops.clone(),
+            expected_res: self.expected_res.clone(),
+            operand_registers: self.operand_registers.clone(),
+            res_registers: self.res_registers.clone(),
+            registers: self.registers.clone(),
+            immediate_rhs: self.immediate_rhs.clone(),
+            public_res: self.public_res.clone(),
+            _marker: PhantomData::default(),
+        }
+    }
+}
+
+impl<F: FieldExt, P: FloorPlanner> fmt::Debug for BinOpCircuit<F, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BinOpCircuit")
+            .field("ops", &self.ops)
+            .field("expected_res", &self.expected_res)
+            .field("operand_registers", &self.operand_registers)
+            .field("res_registers", &self.res_registers)
+            .field("registers", &self.registers)
+            .field("immediate_rhs", &self.immediate_rhs)
+            .field("public_res", &self.public_res)
+            .finish()
+    }
+}
+
+impl<F: FieldExt> BinOpCircuit<F> {
+    pub fn new(op: u64, lhs: u64, rhs: u64, res: u64) -> Self {
+        Self::from_ops(vec![(op, lhs, rhs, res)])
+    }
+
+    /// Same as `new`, with `res` computed by `BinOpTag::eval`, so that the
+    /// circuit is satisfied by construction. Operands wider than the
+    /// `BIT_WIDTH`-bit word are rejected, as the table has no row for them.
+    pub fn new_checked(op: BinOpTag, lhs: u64, rhs: u64) -> Result<Self, TableError> {
+        for value in [lhs, rhs] {
+            to_field::<F>(value.into(), BIT_WIDTH)?;
+        }
+        let (res, _) = op.eval(lhs, rhs, BIT_WIDTH);
+        Ok(Self::new(op as u64, lhs, rhs, res))
+    }
+
+    pub fn from_ops(ops: Vec<(u64, u64, u64, u64)>) -> Self {
+        let ops = ops
+            .into_iter()
+            .map(|(op, lhs, rhs, res)| {
+                // Any `u64` fits in 64 bits, so the conversions cannot fail.
+                let field = |v: u64| to_field::<F>(v.into(), u64::BITS).unwrap();
+                let tag = BinOpTag::try_from(op).ok();
+                let encoded_res = tag
+                    .map(|tag| Encoded::from_res(tag, res))
+                    .unwrap_or_else(|| Encoded::from_unsigned(res))
+                    .inner();
+                let is_error = matches!(tag, Some(tag) if tag.is_error(rhs)) as u64;
+                let [op, lhs, rhs, is_error] = [op, lhs, rhs, is_error].map(field);
+                [op, lhs, rhs, encoded_res, is_error]
+            })
+            .collect::<Vec<_>>();
+        Self {
+            expected_res: vec![None; ops.len()],
+            operand_registers: vec![None; ops.len()],
+            res_registers: vec![None; ops.len()],
+            registers: vec![],
+            immediate_rhs: vec![None; ops.len()],
+            public_res: ops.iter().map(|op| op[3]).collect(),
+            ops: ops.into_iter().map(|op| op.map(Value::known)).collect(),
+            _marker: PhantomData::default(),
+        }
+    }
+}
+
+impl<F: FieldExt, P: FloorPlanner> BinOpCircuit<F, P> {
+    /// The same witness, laid out by the floor planner `Q`.
+    pub fn with_floor_planner<Q: FloorPlanner>(self) -> BinOpCircuit<F, Q> {
+        BinOpCircuit {
+            ops: self.ops,
+            expected_res: self.expected_res,
+            operand_registers: self.operand_registers,
+            res_registers: self.res_registers,
+            registers: self.registers,
+            immediate_rhs: self.immediate_rhs,
+            public_res: self.public_res,
+            _marker: PhantomData::default(),
+        }
+    }
+
+    /// Binds the result of the `index`-th operation to `res`, encoded as
+    /// the op encodes its results, see `Encoded::from_res`.
+    pub fn expect_res(mut self, index: usize, res: Encoded<F>) -> Self {
+        self.expected_res[index] = Some(Value::known(res.inner()));
+        self
+    }
+
+    /// Sets the register values, by index.
+    pub fn with_registers(mut self, values: Vec<u64>) -> Self {
+        self.registers = values
+            .into_iter()
+            .map(|value| Value::known(F::from(value)))
+            .collect();
+        self
+    }
+
+    /// Reads the operands of the `index`-th operation from the registers
+    /// `lhs_index` and `rhs_index`.
+    pub fn read_operands(mut self, index: usize, lhs_index: u64, rhs_index: u64) -> Self {
+        self.operand_registers[index] = Some((
+            Value::known(F::from(lhs_index)),
+            Value::known(F::from(rhs_index)),
+        ));
+        self
+    }
+
+    /// Writes the result of the `index`-th operation to the register
+    /// `res_index`.
+    pub fn write_res(mut self, index: usize, res_index: u64) -> Self {
+        self.res_registers[index] = Some(Value::known(F::from(res_index)));
+        self
+    }
+
+    /// Binds the `rhs` of the `index`-th operation to the immediate
+    /// `immediate`, see `BinOpConfig::assign_immediate_rhs`.
+    pub fn immediate_rhs(mut self, index: usize, immediate: u64) -> Self {
+        self.immediate_rhs[index] = Some(F::from(immediate));
+        self
+    }
+
+    /// Number of operations, i.e. of rows of the instance column.
+    pub fn num_ops(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// Appends `0 + 0 = 0` operations up to `capacity`, so that circuits of
+    /// fewer operations have the shape of one of `capacity`: the same rows
+    /// enable `q_enable` and are copied to the instance column. Their public
+    /// results are 0, as the rows of the instance column left out by the
+    /// verifier are.
+    ///
+    /// Fails if the circuit already holds more than `capacity` operations.
+    pub fn pad_ops(mut self, capacity: usize) -> Result<Self, TableError> {
+        if self.ops.len() > capacity {
+            return Err(TableError::TooManyRows {
+                table: "binop",
+                rows: self.ops.len(),
+                capacity,
+            });
+        }
+        let padding = [BinOpTag::ADD as u64, 0, 0, 0, 0].map(|value| Value::known(F::from(value)));
+        let missing = capacity - self.ops.len();
+        self.ops.extend(vec![padding; missing]);
+        self.expected_res.extend(vec![None; missing]);
+        self.operand_registers.extend(vec![None; missing]);
+        self.res_registers.extend(vec![None; missing]);
+        self.immediate_rhs.extend(vec![None; missing]);
+        self.public_res.extend(vec![F::zero(); missing]);
+        Ok(self)
+    }
+
+    /// Whether an operation enables a selector or assigns a fixed cell
+    /// beyond the ones of `from_ops`, i.e. reads or writes a register, has an
+    /// immediate `rhs` or an expected `res`.
+    pub fn has_row_options(&self) -> bool {
+        self.expected_res.iter().any(Option::is_some)
+            || self.operand_registers.iter().any(Option::is_some)
+            || self.res_registers.iter().any(Option::is_some)
+            || self.immediate_rhs.iter().any(Option::is_some)
+    }
+
+    /// Names the operations whose `(tag, lhs, rhs, res)` is missing from
+    /// the binop table, one line per `binop_lookup` failure of `prover`,
+    /// e.g. `binop_lookup failed on operation 0: (+, 3, 4, 8)`. Empty if
+    /// `prover` verifies.
+    ///
+    /// A failure only locates the row by its offset in the operation
+    /// region, so the tuple is decoded from the witness of the circuit.
+    pub fn explain_lookup_failure(&self, prover: &MockProver<F>) -> String {
+        let failures = match prover.verify() {
+            Ok(()) => return String::new(),
+            Err(failures) => failures,
+        };
+        failures
+            .iter()
+            .filter_map(|failure| match failure {
+                VerifyFailure::Lookup {
+                    name,
+                    location: FailureLocation::InRegion { offset, .. },
+                    ..
+                } if *name == BINOP_LOOKUP => self.ops.get(*offset).map(|op| (*offset, op)),
+                _ => None,
+            })
+            .map(|(offset, [op, lhs, rhs, res, ..])| {
+                let tag = decode_value(*op);
+                let tag = tag
+                    .parse::<u8>()
+                    .ok()
+                    .and_then(|tag| BinOpTag::try_from(tag).ok())
+                    .map_or(tag, |tag| tag.to_string());
+                let [lhs, rhs, res] = [lhs, rhs, res].map(|value| decode_value(*value));
+                format!("binop_lookup failed on operation {offset}: ({tag}, {lhs}, {rhs}, {res})")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// The op whose tag is `op`, if any. Compared as field elements, so that
+/// no element but the tag itself decodes to it.
+fn decode_tag<F: FieldExt>(op: F) -> Option<BinOpTag> {
+    BinOpTag::iter().find(|tag| F::from(*tag as u64) == op)
+}
+
+/// A small field element as its integer, negative for the encoding of
+/// `encode_binop_res`, or `?` if unknown.
+fn decode_value<F: FieldExt>(value: Value<F>) -> String {
+    let mut decoded = String::from("?");
+    value.map(|value| {
+        let low = value.get_lower_128();
+        decoded = if F::from_u128(low) == value {
+            low.to_string()
+        } else {
+            format!("-{}", (-value).get_lower_128())
+        };
+    });
+    decoded
+}
+
+/// Smallest `k` whose `2^k` rows fit the table `BinOpConfig` loads at
+/// `range` bits, a single operation and the blinding rows.
+///
+/// Counts the rows the same way as `BinOpCircuit::min_num_rows`, so it can
+/// be used to pick the `k` given to `MockProver::run` or the setup. See
+/// `min_k_for_binop_ops` for circuits of more operations.
+pub fn min_k_for_binop(range: u32) -> u32 {
+    min_k_for_binop_ops(range, 1)
+}
+
+/// Same as `min_k_for_binop`, with room for `num_ops` operations.
+pub fn min_k_for_binop_ops(range: u32, num_ops: usize) -> u32 {
+    let rows = padded_len(generate_arith_table(range).len()) + num_ops + BLINDING_ROWS;
+    rows.next_power_of_two().trailing_zeros()
+}
+
+/// Binops as executed by the VM, `(op, lhs, rhs)` in order, with the results
+/// left to `BinOpTag::eval`.
+#[derive(Clone, Debug, Default)]
+pub struct ExecutionTrace {
+    pub ops: Vec<(BinOpTag, u64, u64)>,
+}
+
+impl ExecutionTrace {
+    pub fn new(ops: Vec<(BinOpTag, u64, u64)>) -> Self {
+        Self { ops }
+    }
+
+    /// `(res, is_error)` of every op on `BIT_WIDTH`-bit operands. A trapping
+    /// op, such as a division by zero, has a `res` of 0.
+    ///
+    /// Operands wider than `BIT_WIDTH` bits are rejected, as in
+    /// `BinOpCircuit::new_checked`.
+    pub fn results(&self) -> Result<Vec<(u64, bool)>, TableError> {
+        self.ops
+            .iter()
+            .map(|(op, lhs, rhs)| {
+                for value in [*lhs, *rhs] {
+                    if value > max_value(BIT_WIDTH) {
+                        return Err(TableError::ValueTooWide {
+                            value: value.into(),
+                            bits: BIT_WIDTH,
+                        });
+                    }
+                }
+                Ok(op.eval(*lhs, *rhs, BIT_WIDTH))
+            })
+            .collect()
+    }
+
+    /// The `BinOpCircuit` witnessing the trace. Traps are witnessed with
+    /// `is_error` set, so they are proved rather than rejected.
+    pub fn witness<F: FieldExt>(&self) -> Result<BinOpCircuit<F>, TableError> {
+        let ops = self
+            .ops
+            .iter()
+            .zip(self.results()?)
+            .map(|((op, lhs, rhs), (res, _))| (*op as u64, *lhs, *rhs, res))
+            .collect();
+        Ok(BinOpCircuit::from_ops(ops))
+    }
+}
+
+impl<F: FieldExt, P: FloorPlanner> SubCircuit<F> for BinOpCircuit<F, P> {
     type Config = BinOpConfig<F>;
 
+    fn instance(&self) -> Vec<Vec<F>> {
+        vec![self.public_res.clone()]
+    }
+
+    /// The precomputed table and the witnessed operations are assigned in
+    /// separate regions.
+    fn min_num_rows(&self) -> usize {
+        padded_len(generate_arith_table(BIT_WIDTH).len()) + self.ops.len()
+    }
+
     fn synthesize_sub(
         &self,
         config: &Self::Config,
         layouter: &mut impl Layouter<F>,
     ) -> Result<(), Error> {
         config.load_binop_table(layouter)?;
+        let res_cells = layouter.assign_region(
+            || "binop",
+            |mut region| {
+                let mut res_cells = vec![];
+                for (offset, [op, lhs, rhs, res, is_error]) in self.ops.iter().enumerate() {
+                    config.check_binop_row(offset, *op)?;
+                    let carry = config.carry_witness(*op, *lhs, *rhs);
+                    let res_cell = config.assign_binop_row(
+                        &mut region,
+                        offset,
+                        *op,
+                        *lhs,
+                        *rhs,
+                        *res,
+                        *is_error,
+                        carry,
+                        self.expected_res[offset],
+                        self.operand_registers[offset],
+                    )?;
+                    if let Some(res_index) = self.res_registers[offset] {
+                        config.assign_res_register(&mut region, offset, res_index)?;
+                    }
+                    if let Some(immediate) = self.immediate_rhs[offset] {
+                        config.assign_immediate_rhs(&mut region, offset, immediate)?;
+                    }
+                    res_cells.push(res_cell);
+                }
+                enable_rows(&mut region, &config.q_enable, 0..self.ops.len())?;
+                Ok(res_cells)
+            },
+        )?;
+        for (row, res_cell) in res_cells.iter().enumerate() {
+            layouter.constrain_instance(res_cell.cell(), config.instance, row)?;
+        }
         Ok(())
     }
 }
+
+impl<F: FieldExt, P: FloorPlanner> Circuit<F> for BinOpCircuit<F, P> {
+    type Config = BinOpConfig<F>;
+    type FloorPlanner = P;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            ops: vec![[Value::unknown(); 5]; self.ops.len()],
+            expected_res: self
+                .expected_res
+                .iter()
+                .map(|expected_res| expected_res.map(|_| Value::unknown()))
+                .collect(),
+            operand_registers: self
+                .operand_registers
+                .iter()
+                .map(|registers| registers.map(|_| (Value::unknown(), Value::unknown())))
+                .collect(),
+            res_registers: self
+                .res_registers
+                .iter()
+                .map(|register| register.map(|_| Value::unknown()))
+                .collect(),
+            registers: vec![Value::unknown(); self.registers.len()],
+            immediate_rhs: self.immediate_rhs.clone(),
+            public_res: self.public_res.clone(),
+            _marker: PhantomData::default(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let binop_table = BinaryOperationTable::construct(meta, BIT_WIDTH);
+        let register_table = RegisterTable::construct(meta, NUM_REGISTERS);
+        BinOpConfig::new(
+            meta,
+            BinOpConfigArgs {
+                binop_table,
+                register_table,
+                pool: ColumnPool::default(),
+            },
+        )
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        config.load_register_table(&mut layouter, self.registers.clone())?;
+        self.synthesize_sub(&config, &mut layouter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::floor_planner::V1, dev::MockProver, halo2curves::bn256::Fr};
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::backend::halo2::table::{encode_binop_res, generate_binop_table, BinopLookupMap};
+    use crate::backend::halo2::utils::{assert_fails, assert_satisfied};
+
+    #[test]
+    fn circuit_test() {
+        let circuit = BinOpCircuit::<Fr>::new(BinOpTag::ADD as u64, 1, 2, 3);
+        assert_satisfied(&circuit, 14);
+        assert_fails(&BinOpCircuit::<Fr>::new(BinOpTag::ADD as u64, 1, 2, 4), 14);
+    }
+
+    #[test]
+    fn circuit_test_new_checked() {
+        let circuit = BinOpCircuit::<Fr>::new_checked(BinOpTag::ADD, 2, 3).unwrap();
+        assert_eq!(circuit.instance(), vec![vec![Fr::from(5)]]);
+        assert_satisfied(&circuit, 14);
+
+        assert!(matches!(
+            BinOpCircuit::<Fr>::new_checked(BinOpTag::ADD, 16, 3),
+            Err(TableError::ValueTooWide {
+                value: 16,
+                bits: BIT_WIDTH
+            })
+        ));
+    }
+
+    #[test]
+    fn circuit_test_pad_ops() {
+        let circuit = BinOpCircuit::<Fr>::new(BinOpTag::ADD as u64, 1, 2, 3)
+            .pad_ops(3)
+            .unwrap();
+        assert_eq!(circuit.num_ops(), 3);
+        assert_eq!(
+            circuit.instance(),
+            vec![vec![Fr::from(3), Fr::zero(), Fr::zero()]]
+        );
+        assert_satisfied(&circuit, 14);
+
+        let circuit = BinOpCircuit::<Fr>::new(BinOpTag::ADD as u64, 1, 2, 3);
+        assert!(matches!(
+            circuit.pad_ops(0),
+            Err(TableError::TooManyRows { rows: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn circuit_test_floor_planners() {
+        let k = 14;
+        let circuit = BinOpCircuit::<Fr>::from_ops(vec![
+            (BinOpTag::ADD as u64, 1, 2, 3),
+            (BinOpTag::LT as u64, 2, 7, 1),
+        ])
+        .with_registers(vec![1, 2])
+        .read_operands(0, 0, 1);
+        let instance = circuit.instance();
+        let prover = MockProver::run(k, &circuit, instance.clone()).unwrap();
+        prover.assert_satisfied();
+
+        let circuit = circuit.with_floor_planner::<V1>();
+        let prover = MockProver::run(k, &circuit, instance).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn circuit_test_public_res() {
+        let k = 14;
+        let circuit = BinOpCircuit::<Fr>::new(BinOpTag::ADD as u64, 1, 2, 3);
+        assert_eq!(circuit.instance(), vec![vec![Fr::from(3)]]);
+        let prover = MockProver::run(k, &circuit, vec![vec![Fr::from(3)]]).unwrap();
+        prover.assert_satisfied();
+
+        let prover = MockProver::run(k, &circuit, vec![vec![Fr::from(4)]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn circuit_test_div_by_zero() {
+        let k = 14;
+        let circuit = BinOpCircuit::<Fr>::new(BinOpTag::DIV as u64, 5, 0, 0);
+        let prover = MockProver::run(k, &circuit, circuit.instance()).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn circuit_test_register_operands() {
+        let k = 14;
+        let circuit = BinOpCircuit::<Fr>::new(BinOpTag::ADD as u64, 1, 2, 3)
+            .with_registers(vec![0, 1, 2])
+            .read_operands(0, 1, 2);
+        let prover = MockProver::run(k, &circuit, circuit.instance()).unwrap();
+        prover.assert_satisfied();
+
+        let circuit = BinOpCircuit::<Fr>::new(BinOpTag::ADD as u64, 1, 2, 3)
+            .with_registers(vec![0, 1, 2])
+            .read_operands(0, 2, 1);
+        let prover = MockProver::run(k, &circuit, circuit.instance()).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn circuit_test_register_zero_read() {
+        let k = 14;
+        // r1 holds 0, so reading 0 from it is fine
+        let circuit = BinOpCircuit::<Fr>::new(BinOpTag::ADD as u64, 0, 0, 0)
+            .with_registers(vec![5, 0])
+            .read_operands(0, 1, 1);
+        let prover = MockProver::run(k, &circuit, circuit.instance()).unwrap();
+        prover.assert_satisfied();
+
+        // r0 holds 5, but unassigned rows of the table also read as (0, 0)
+        let circuit = BinOpCircuit::<Fr>::new(BinOpTag::ADD as u64, 0, 0, 0)
+            .with_registers(vec![5, 0])
+            .read_operands(0, 0, 1);
+        let prover = MockProver::run(k, &circuit, circuit.instance()).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn circuit_test_register_zero_write() {
+        let k = 14;
+        let circuit = |registers| {
+            BinOpCircuit::<Fr>::new(BinOpTag::ADD as u64, 0, 0, 0)
+                .with_registers(registers)
+                .write_res(0, 0)
+        };
+        let valid = circuit(vec![0]);
+        let prover = MockProver::run(k, &valid, valid.instance()).unwrap();
+        prover.assert_satisfied();
+
+        // r0 holds 5, but unassigned rows of the table also read as (0, 0)
+        let invalid = circuit(vec![5]);
+        let prover = MockProver::run(k, &invalid, invalid.instance()).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn circuit_test_register_write_then_read() {
+        let k = 14;
+        // r2 = r0 + r1, then r3 = r2 + r1
+        let ops = vec![
+            (BinOpTag::ADD as u64, 1, 2, 3),
+            (BinOpTag::ADD as u64, 3, 2, 5),
+        ];
+        let circuit = |registers| {
+            BinOpCircuit::<Fr>::from_ops(ops.clone())
+                .with_registers(registers)
+                .read_operands(0, 0, 1)
+                .write_res(0, 2)
+                .read_operands(1, 2, 1)
+                .write_res(1, 3)
+        };
+        let valid = circuit(vec![1, 2, 3, 5]);
+        let prover = MockProver::run(k, &valid, valid.instance()).unwrap();
+        prover.assert_satisfied();
+
+        // r2 does not hold the result written to it
+        let invalid = circuit(vec![1, 2, 4, 5]);
+        let prover = MockProver::run(k, &invalid, invalid.instance()).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn circuit_test_immediate_rhs() {
+        let k = 14;
+        // x + 5, with the 5 an immediate
+        let circuit = BinOpCircuit::<Fr>::new(BinOpTag::ADD as u64, 3, 5, 8).immediate_rhs(0, 5);
+        let prover = MockProver::run(k, &circuit, circuit.instance()).unwrap();
+        prover.assert_satisfied();
+
+        // (ADD, 3, 4, 7) is a table row, but 4 is not the immediate
+        let circuit = BinOpCircuit::<Fr>::new(BinOpTag::ADD as u64, 3, 4, 7).immediate_rhs(0, 5);
+        let prover = MockProver::run(k, &circuit, circuit.instance()).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn circuit_test_comparison() {
+        let k = 14;
+        let circuit = BinOpCircuit::<Fr>::from_ops(vec![
+            (BinOpTag::LT as u64, 2, 3, 1),
+            (BinOpTag::LT as u64, 3, 3, 0),
+            (BinOpTag::GT as u64, 15, 0, 1),
+            (BinOpTag::GT as u64, 0, 15, 0),
+            (BinOpTag::LE as u64, 3, 3, 1),
+            (BinOpTag::GE as u64, 2, 3, 0),
+            (BinOpTag::EQ as u64, 7, 7, 1),
+            (BinOpTag::EQ as u64, 7, 8, 0),
+        ]);
+        let prover = MockProver::run(k, &circuit, circuit.instance()).unwrap();
+        prover.assert_satisfied();
+
+        for op in [
+            (BinOpTag::LT as u64, 2, 3, 0),
+            (BinOpTag::GT as u64, 3, 3, 1),
+            (BinOpTag::EQ as u64, 7, 8, 1),
+            // operands wider than the table
+            (BinOpTag::LT as u64, 16, 17, 1),
+        ] {
+            let circuit = BinOpCircuit::<Fr>::from_ops(vec![op]);
+            let prover = MockProver::run(k, &circuit, circuit.instance()).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+
+    #[test]
+    fn explain_missing_binop_row() {
+        let circuit = BinOpCircuit::<Fr>::from_ops(vec![
+            (BinOpTag::ADD as u64, 1, 2, 3),
+            (BinOpTag::ADD as u64, 3, 4, 8),
+        ]);
+        let prover = MockProver::run(14, &circuit, circuit.instance()).unwrap();
+        let explanation = circuit.explain_lookup_failure(&prover);
+        assert!(explanation.contains("operation 1: (+, 3, 4, 8)"));
+        assert!(!explanation.contains("operation 0"));
+
+        let circuit = BinOpCircuit::<Fr>::new(BinOpTag::ADD as u64, 3, 4, 7);
+        let prover = MockProver::run(14, &circuit, circuit.instance()).unwrap();
+        assert_eq!(circuit.explain_lookup_failure(&prover), "");
+
+        // only a register lookup fails, which is not a missing binop row
+        let circuit = BinOpCircuit::<Fr>::new(BinOpTag::ADD as u64, 0, 0, 0)
+            .with_registers(vec![5, 0])
+            .read_operands(0, 0, 1);
+        let prover = MockProver::run(14, &circuit, circuit.instance()).unwrap();
+        assert!(prover.verify().is_err());
+        assert_eq!(circuit.explain_lookup_failure(&prover), "");
+    }
+
+    #[test]
+    fn circuit_test_comparison_res_is_boolean() {
+        let circuit = BinOpCircuit::<Fr>::from_ops(vec![(BinOpTag::LT as u64, 2, 3, 2)]);
+        let prover = MockProver::run(14, &circuit, circuit.instance()).unwrap();
+        let failures = prover.verify().unwrap_err();
+        // rejected by the gate, not only by the comparison lookup
+        assert!(failures.iter().any(|failure| failure
+            .to_string()
+            .contains("binop comparison res is boolean")));
+
+        // a non-comparison op may have a res of 2
+        let circuit = BinOpCircuit::<Fr>::new(BinOpTag::ADD as u64, 1, 1, 2);
+        let prover = MockProver::run(14, &circuit, circuit.instance()).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn circuit_test_shift_amount() {
+        let k = 14;
+        let table = BinopLookupMap::from_rows(generate_arith_table(BIT_WIDTH));
+        for op in [BinOpTag::SHL, BinOpTag::SHR] {
+            let (res, _) = op.eval(8, BIT_WIDTH as u64 - 1, BIT_WIDTH);
+            let circuit =
+                BinOpCircuit::<Fr>::from_ops(vec![(op as u64, 8, BIT_WIDTH as u64 - 1, res)]);
+            let prover = MockProver::run(k, &circuit, circuit.instance()).unwrap();
+            prover.assert_satisfied();
+
+            // (op, 8, BIT_WIDTH, 0) is a table row, but shifts the word out
+            assert!(table.contains_row([op as u64, 8, BIT_WIDTH as u64, 0]));
+            let circuit = BinOpCircuit::<Fr>::from_ops(vec![(op as u64, 8, BIT_WIDTH as u64, 0)]);
+            let prover = MockProver::run(k, &circuit, circuit.instance()).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+
+    #[test]
+    fn execution_trace_witness() {
+        let trace = ExecutionTrace::new(vec![
+            (BinOpTag::ADD, 3, 5),
+            (BinOpTag::DIV, 7, 0),
+            (BinOpTag::LT, 2, 3),
+            (BinOpTag::SHL, 1, 2),
+        ]);
+        assert_eq!(
+            trace.results().unwrap(),
+            vec![(8, false), (0, true), (1, false), (4, false)]
+        );
+        let circuit = trace.witness::<Fr>().unwrap();
+        let prover = MockProver::run(14, &circuit, circuit.instance()).unwrap();
+        prover.assert_satisfied();
+
+        let trace = ExecutionTrace::new(vec![(BinOpTag::ADD, 100, 1)]);
+        assert!(matches!(
+            trace.witness::<Fr>(),
+            Err(TableError::ValueTooWide {
+                value: 100,
+                bits: BIT_WIDTH
+            })
+        ));
+    }
+
+    /// Up to 8 ops of any tag on `BIT_WIDTH`-bit operands. Shift amounts
+    /// stay below `BIT_WIDTH`, as `binop_shift_amount_range` rejects the
+    /// others, see `circuit_test_shift_amount`.
+    fn random_trace() -> impl Strategy<Value = Vec<(BinOpTag, u8, u8)>> {
+        let operand = 0..1u8 << BIT_WIDTH;
+        let op = (
+            prop::sample::select(BinOpTag::iter().collect::<Vec<_>>()),
+            operand.clone(),
+            operand,
+        )
+            .prop_map(|(op, lhs, rhs)| match op {
+                BinOpTag::SHL | BinOpTag::SHR => (op, lhs, rhs % BIT_WIDTH as u8),
+                _ => (op, lhs, rhs),
+            });
+        prop::collection::vec(op, 1..=8)
+    }
+
+    proptest! {
+        // every case runs the mock prover twice over the whole table
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn random_trace_replays(ops in random_trace(), corrupt in any::<prop::sample::Index>()) {
+            let trace = ExecutionTrace::new(
+                ops.iter()
+                    .map(|&(op, lhs, rhs)| (op, lhs.into(), rhs.into()))
+                    .collect(),
+            );
+            let mut circuit = trace.witness::<Fr>().unwrap();
+            let prover = MockProver::run(14, &circuit, circuit.instance()).unwrap();
+            prop_assert!(prover.verify().is_ok());
+
+            // the public result follows the corrupted one, so that only the
+            // op constraints can reject it
+            let index = corrupt.index(ops.len());
+            circuit.ops[index][3] = circuit.ops[index][3] + Value::known(Fr::from(1));
+            circuit.public_res[index] += Fr::from(1);
+            let prover = MockProver::run(14, &circuit, circuit.instance()).unwrap();
+            prop_assert!(prover.verify().is_err());
+        }
+    }
+
+    #[test]
+    fn min_num_rows_covers_table() {
+        let circuit = BinOpCircuit::<Fr>::from_ops(vec![(BinOpTag::ADD as u64, 1, 2, 3); 2]);
+        assert!(circuit.min_num_rows() > generate_arith_table(BIT_WIDTH).len());
+    }
+
+    #[test]
+    fn min_k_fits_circuit() {
+        let circuit = BinOpCircuit::<Fr>::new(BinOpTag::ADD as u64, 1, 2, 3);
+        let k = min_k_for_binop(BIT_WIDTH);
+        // the 4352 rows of the 4-bit table and its padding row fit in 2^13
+        assert_eq!(k, 13);
+        let prover = MockProver::run(k, &circuit, circuit.instance()).unwrap();
+        prover.assert_satisfied();
+        assert!(MockProver::run(k - 1, &circuit, circuit.instance()).is_err());
+
+        assert_eq!(min_k_for_binop_ops(BIT_WIDTH, 1), k);
+        // 2^13 operations no longer fit next to the table
+        assert_eq!(min_k_for_binop_ops(BIT_WIDTH, 1 << 13), 14);
+    }
+
+    #[test]
+    fn binop_config_stats() {
+        let stats = BinOpConfig::describe(&mut ConstraintSystem::<Fr>::default());
+        // the binop table lookup, the comparison table lookup, the tag
+        // lookup, 4 range checks and 3 register lookups
+        assert_eq!(stats.num_lookups, 10);
+        // 5 of them for the comparison tag gadgets
+        assert_eq!(stats.num_advice_columns, 24);
+        // binop, comparison, range, register and tag tables, immediates
+        assert_eq!(stats.num_fixed_columns, 17);
+        assert_eq!(stats.num_instance_columns, 1);
+        assert_eq!(stats.num_selectors, 5);
+        assert_eq!(stats.num_gates, 12);
+    }
+
+    #[test]
+    fn binop_configs_share_pool() {
+        let num_advice_columns = |shared: bool| {
+            let mut meta = ConstraintSystem::<Fr>::default();
+            let binop_table = BinaryOperationTable::construct(&mut meta, BIT_WIDTH);
+            let register_table = RegisterTable::construct(&mut meta, NUM_REGISTERS);
+            let pool = ColumnPool::new(&mut meta, if shared { 16 } else { 0 });
+            for _ in 0..2 {
+                let args = BinOpConfigArgs {
+                    binop_table: binop_table.clone(),
+                    register_table: register_table.clone(),
+                    pool: pool.clone(),
+                };
+                BinOpConfig::<Fr>::new(&mut meta, args);
+            }
+            meta.num_advice_columns()
+        };
+        assert!(num_advice_columns(true) < num_advice_columns(false));
+    }
+
+    #[test]
+    fn binop_table_generated_once() {
+        let config = BinOpCircuit::<Fr>::configure(&mut ConstraintSystem::default());
+        let shared = config.clone();
+        let calls = std::cell::Cell::new(0);
+        let generate = |range| {
+            calls.set(calls.get() + 1);
+            generate_binop_table(range, range)
+        };
+        let rows = config.precomputed_binop_with(generate).len();
+        assert_eq!(shared.precomputed_binop_with(generate).len(), rows);
+        assert_eq!(config.precomputed_binop().len(), rows);
+        assert_eq!(calls.get(), 1);
+    }
+
+    /// Assigns an operation at `offset` of a config with room for 2 rows.
+    struct OffsetCircuit {
+        offset: usize,
+        result: std::cell::RefCell<Option<Result<(), TableError>>>,
+    }
+
+    impl Circuit<Fr> for OffsetCircuit {
+        type Config = BinOpConfig<Fr>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                offset: self.offset,
+                result: Default::default(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            BinOpCircuit::<Fr>::configure(meta).with_capacity(2)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "binop",
+                |mut region| {
+                    let [op, lhs, rhs, res, is_error, carry] =
+                        [BinOpTag::ADD as u64, 1, 2, 3, 0, 0].map(|v| Value::known(Fr::from(v)));
+                    let result = config.load_binop_row_checked(
+                        &mut region,
+                        self.offset,
+                        op,
+                        lhs,
+                        rhs,
+                        res,
+                        is_error,
+                        carry,
+                        None,
+                        None,
+                    );
+                    self.result.replace(Some(result.map(|_| ())));
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn load_binop_row_offset_out_of_range() {
+        let run = |offset| {
+            let circuit = OffsetCircuit {
+                offset,
+                result: Default::default(),
+            };
+            MockProver::run(14, &circuit, vec![vec![]]).unwrap();
+            circuit.result.into_inner().unwrap()
+        };
+        assert!(run(1).is_ok());
+        assert!(matches!(
+            run(2),
+            Err(TableError::OffsetOutOfRange {
+                offset: 2,
+                capacity: 2
+            })
+        ));
+    }
+
+    /// `BinOpCircuit` followed by a row that is in no table, assigned
+    /// without enabling the row.
+    struct PaddingCircuit(BinOpCircuit<Fr>);
+
+    impl Circuit<Fr> for PaddingCircuit {
+        type Config = BinOpConfig<Fr>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(self.0.without_witnesses())
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            BinOpCircuit::<Fr>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            self.0.synthesize_sub(&config, &mut layouter)?;
+            layouter.assign_region(
+                || "padding",
+                |mut region| {
+                    let columns = [
+                        config.tag_column,
+                        config.lhs_column,
+                        config.rhs_column,
+                        config.res_column,
+                    ];
+                    for (column, value) in columns.into_iter().zip([99, 1, 2, 42]) {
+                        region.assign_advice(
+                            || "padding",
+                            column,
+                            0,
+                            || Value::known(Fr::from(value)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn padding_rows_are_not_looked_up() {
+        let circuit = PaddingCircuit(BinOpCircuit::new(BinOpTag::ADD as u64, 1, 2, 3));
+        let prover = MockProver::run(14, &circuit, circuit.0.instance()).unwrap();
+        prover.assert_satisfied();
+    }
+
+    /// The loaded tables and a single `[tag, lhs, rhs, res, is_error, carry]`
+    /// operation, assigned without the checks of `load_binop_row_checked`.
+    struct RawRowCircuit([u64; 6]);
+
+    impl Circuit<Fr> for RawRowCircuit {
+        type Config = BinOpConfig<Fr>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(self.0)
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            BinOpCircuit::<Fr>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            config.load_binop_table(&mut layouter)?;
+            layouter.assign_region(
+                || "binop",
+                |mut region| {
+                    let [op, lhs, rhs, res, is_error, carry] =
+                        self.0.map(|v| Value::known(Fr::from(v)));
+                    config.load_binop_row(
+                        &mut region,
+                        0,
+                        op,
+                        lhs,
+                        rhs,
+                        res,
+                        is_error,
+                        carry,
+                        None,
+                        None,
+                    )?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    /// Three operations, the second with a wrong `res`, with `q_enable` on
+    /// the rows in `self.0` only.
+    struct EnableRowsCircuit(Vec<usize>);
+
+    impl Circuit<Fr> for EnableRowsCircuit {
+        type Config = BinOpConfig<Fr>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(self.0.clone())
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            BinOpCircuit::<Fr>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            config.load_binop_table(&mut layouter)?;
+            layouter.assign_region(
+                || "binop",
+                |mut region| {
+                    for (offset, res) in [2, 42, 4].into_iter().enumerate() {
+                        let [op, lhs, rhs, res, is_error, carry] =
+                            [BinOpTag::ADD as u64, 1, offset as u64 + 1, res, 0, 0]
+                                .map(|v| Value::known(Fr::from(v)));
+                        config.assign_binop_row(
+                            &mut region,
+                            offset,
+                            op,
+                            lhs,
+                            rhs,
+                            res,
+                            is_error,
+                            carry,
+                            None,
+                            None,
+                        )?;
+                    }
+                    enable_rows(&mut region, &config.q_enable, self.0.iter().copied())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn enable_rows_only_constrains_enabled_rows() {
+        let prover = MockProver::run(14, &EnableRowsCircuit(vec![0, 2]), vec![vec![]]).unwrap();
+        prover.assert_satisfied();
+
+        let prover = MockProver::run(14, &EnableRowsCircuit(vec![0, 1, 2]), vec![vec![]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    /// `self.0` synthesized with a disabled config.
+    struct DisabledCircuit(BinOpCircuit<Fr>);
+
+    impl Circuit<Fr> for DisabledCircuit {
+        type Config = BinOpConfig<Fr>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(self.0.without_witnesses())
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            BinOpCircuit::<Fr>::configure(meta).disabled()
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            assert_eq!(config.table_rows(), 1);
+            self.0.synthesize_sub(&config, &mut layouter)
+        }
+    }
+
+    #[test]
+    fn disabled_config_adds_no_constraints() {
+        let circuit = DisabledCircuit(BinOpCircuit::from_ops(vec![]));
+        let prover = MockProver::run(4, &circuit, vec![vec![]]).unwrap();
+        prover.assert_satisfied();
+
+        let circuit = DisabledCircuit(BinOpCircuit::new(BinOpTag::ADD as u64, 1, 2, 3));
+        let instance = circuit.0.instance();
+        assert!(MockProver::run(4, &circuit, instance).is_err());
+    }
+
+    /// `self.0` with a binop table holding only the canonical rows.
+    struct CanonicalCircuit(BinOpCircuit<Fr>);
+
+    impl Circuit<Fr> for CanonicalCircuit {
+        type Config = BinOpConfig<Fr>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(self.0.without_witnesses())
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            BinOpCircuit::<Fr>::configure(meta).with_canonical_operands()
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let add = BinOpTag::ADD as u64;
+            assert!(config.precomputed_binop().contains(&[add, 3, 5, 8]));
+            assert!(!config.precomputed_binop().contains(&[add, 5, 3, 8]));
+            self.0.synthesize_sub(&config, &mut layouter)
+        }
+    }
+
+    #[test]
+    fn commutative_ops_use_canonical_rows() {
+        let circuit = CanonicalCircuit(BinOpCircuit::from_ops(vec![
+            (BinOpTag::ADD as u64, 5, 3, 8),
+            (BinOpTag::MUL as u64, 3, 2, 6),
+            (BinOpTag::SUB as u64, 5, 3, 2),
+        ]));
+        let prover = MockProver::run(14, &circuit, circuit.0.instance()).unwrap();
+        prover.assert_satisfied();
+
+        let circuit = CanonicalCircuit(BinOpCircuit::new(BinOpTag::ADD as u64, 5, 3, 9));
+        let prover = MockProver::run(14, &circuit, circuit.0.instance()).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn undefined_tag_fails() {
+        let row = |tag: u64| RawRowCircuit([tag, 1, 2, 3, 0, 0]);
+        let prover = MockProver::run(14, &row(BinOpTag::ADD as u64), vec![vec![]]).unwrap();
+        prover.assert_satisfied();
+
+        for tag in [0, 99] {
+            let prover = MockProver::run(14, &row(tag), vec![vec![]]).unwrap();
+            let failures = prover.verify().unwrap_err();
+            assert!(
+                failures
+                    .iter()
+                    .any(|failure| failure.to_string().contains("binop_tag_lookup")),
+                "{tag}"
+            );
+        }
+    }
+
+    /// `ADD 1 2 3` whose `res` is copy constrained to register 5, which
+    /// holds `self.0`.
+    struct CopyToRegisterCircuit(u64);
+
+    impl Circuit<Fr> for CopyToRegisterCircuit {
+        type Config = BinOpConfig<Fr>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(self.0)
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            BinOpCircuit::<Fr>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            config.load_binop_table(&mut layouter)?;
+            let register_cell = layouter.assign_region(
+                || "register table",
+                |mut region| {
+                    let mut register_cell = None;
+                    for offset in 0..NUM_REGISTERS {
+                        let value = if offset == 5 { self.0 } else { 0 };
+                        let index = Value::known(Fr::from(offset as u64));
+                        let row = (index, Value::known(Fr::from(value)));
+                        let cell = config.register_table.assign(&mut region, offset, row)?;
+                        if offset == 5 {
+                            register_cell = Some(cell);
+                        }
+                    }
+                    Ok(register_cell.unwrap())
+                },
+            )?;
+            layouter.assign_region(
+                || "binop",
+                |mut region| {
+                    let [op, lhs, rhs, res, is_error, carry] =
+                        [BinOpTag::ADD as u64, 1, 2, 3, 0, 0].map(|v| Value::known(Fr::from(v)));
+                    let res_cell = config.load_binop_row(
+                        &mut region,
+                        0,
+                        op,
+                        lhs,
+                        rhs,
+                        res,
+                        is_error,
+                        carry,
+                        None,
+                        None,
+                    )?;
+                    region.constrain_equal(res_cell.cell(), register_cell.cell())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn res_copied_to_register() {
+        let prover = MockProver::run(14, &CopyToRegisterCircuit(3), vec![vec![]]).unwrap();
+        prover.assert_satisfied();
+
+        let prover = MockProver::run(14, &CopyToRegisterCircuit(4), vec![vec![]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    struct Width2Circuit(BinOpCircuit<Fr>);
+
+    impl Circuit<Fr> for Width2Circuit {
+        type Config = BinOpConfig<Fr>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(self.0.without_witnesses())
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let binop_table = BinaryOperationTable::construct(meta, 2);
+            let register_table = RegisterTable::construct(meta, NUM_REGISTERS);
+            BinOpConfig::new(
+                meta,
+                BinOpConfigArgs {
+                    binop_table,
+                    register_table,
+                    pool: ColumnPool::default(),
+                },
+            )
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            self.0.synthesize_sub(&config, &mut layouter)
+        }
+    }
+
+    #[test]
+    fn circuit_test_width_2() {
+        // the carry is derived at the 2-bit width of the config
+        let circuit = Width2Circuit(BinOpCircuit::new(BinOpTag::ADD as u64, 3, 2, 1));
+        let prover = MockProver::run(10, &circuit, circuit.0.instance()).unwrap();
+        prover.assert_satisfied();
+
+        // 4 is outside of the 2-bit domain
+        assert!(!BinopLookupMap::new(2).contains_row([BinOpTag::ADD as u64, 4, 1, 5]));
+        let circuit = Width2Circuit(BinOpCircuit::new(BinOpTag::ADD as u64, 4, 1, 5));
+        let prover = MockProver::run(10, &circuit, circuit.0.instance()).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    /// `BinOpCircuit` on bytes, with only `SHL` allowed.
+    struct Width8ShlCircuit(BinOpCircuit<Fr>);
+
+    impl Circuit<Fr> for Width8ShlCircuit {
+        type Config = BinOpConfig<Fr>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(self.0.without_witnesses())
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let binop_table = BinaryOperationTable::construct(meta, 8);
+            let register_table = RegisterTable::construct(meta, NUM_REGISTERS);
+            let args = BinOpConfigArgs {
+                binop_table,
+                register_table,
+                pool: ColumnPool::default(),
+            };
+            BinOpConfig::new(meta, args).with_ops(&[BinOpTag::SHL])
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            self.0.synthesize_sub(&config, &mut layouter)
+        }
+    }
+
+    #[test]
+    fn circuit_test_shl_width_8() {
+        // the 2^16 `SHL` rows, padded
+        let k = 18;
+        let ops = (0..8)
+            .map(|shift| {
+                assert_eq!(BinOpTag::SHL.eval(1, shift, 8), (1 << shift, false));
+                (BinOpTag::SHL as u64, 1, shift, 1 << shift)
+            })
+            .collect();
+        let circuit = Width8ShlCircuit(BinOpCircuit::from_ops(ops));
+        let prover = MockProver::run(k, &circuit, circuit.0.instance()).unwrap();
+        prover.assert_satisfied();
+
+        // the table maps a shift by the width to 0, the range gate rejects it
+        assert_eq!(BinOpTag::SHL.eval(1, 8, 8), (0, false));
+        let circuit = Width8ShlCircuit(BinOpCircuit::new(BinOpTag::SHL as u64, 1, 8, 0));
+        let prover = MockProver::run(k, &circuit, circuit.0.instance()).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    /// `BinOpCircuit` with only `ADD` allowed.
+    struct AddOnlyCircuit(BinOpCircuit<Fr>);
+
+    impl Circuit<Fr> for AddOnlyCircuit {
+        type Config = BinOpConfig<Fr>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(self.0.without_witnesses())
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            BinOpCircuit::<Fr>::configure(meta).with_ops(&[BinOpTag::ADD])
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            self.0.synthesize_sub(&config, &mut layouter)
+        }
+    }
+
+    #[test]
+    fn circuit_test_add_only() {
+        let config = BinOpCircuit::<Fr>::configure(&mut ConstraintSystem::default());
+        let add_only = config.clone().with_ops(&[BinOpTag::ADD]);
+        let rows = add_only.precomputed_binop();
+        assert_eq!(rows.len(), 1 << (2 * BIT_WIDTH));
+        assert!(rows.iter().all(|row| row[0] == BinOpTag::ADD as u64));
+        assert!(!rows.iter().any(|row| row[0] == BinOpTag::MUL as u64));
+        assert!(rows.len() < config.precomputed_binop().len());
+        assert!(add_only.binop_table.supports(BinOpTag::ADD));
+        assert!(!add_only.binop_table.supports(BinOpTag::MUL));
+
+        // the table no longer needs `k = 14`
+        let circuit = AddOnlyCircuit(BinOpCircuit::new(BinOpTag::ADD as u64, 1, 2, 3));
+        let prover = MockProver::run(10, &circuit, circuit.0.instance()).unwrap();
+        prover.assert_satisfied();
+
+        let circuit = AddOnlyCircuit(BinOpCircuit::new(BinOpTag::MUL as u64, 3, 4, 12));
+        assert!(MockProver::run(10, &circuit, circuit.0.instance()).is_err());
+    }
+
+    #[test]
+    fn circuit_test_carry() {
+        // 15 + 1 wraps to the 4-bit 0, with a carry
+        let circuit = BinOpCircuit::<Fr>::new(BinOpTag::ADD as u64, 15, 1, 0);
+        let prover = MockProver::run(14, &circuit, circuit.instance()).unwrap();
+        prover.assert_satisfied();
+
+        let circuit = BinOpCircuit::<Fr>::new(BinOpTag::ADD as u64, 15, 1, 16);
+        let prover = MockProver::run(14, &circuit, circuit.instance()).unwrap();
+        assert!(prover.verify().is_err());
+
+        // a carry that does not match the operands
+        let add = BinOpTag::ADD as u64;
+        for row in [[add, 15, 1, 0, 0, 0], [add, 14, 1, 15, 0, 1]] {
+            let prover = MockProver::run(14, &RawRowCircuit(row), vec![vec![]]).unwrap();
+            assert!(prover.verify().is_err(), "{row:?}");
+        }
+    }
+
+    #[test]
+    fn circuit_test_batch() {
+        let circuit = BinOpCircuit::<Fr>::from_ops(vec![
+            (BinOpTag::ADD as u64, 1, 2, 3),
+            (BinOpTag::MUL as u64, 3, 4, 12),
+            (BinOpTag::SUB as u64, 9, 4, 5),
+            (BinOpTag::LT as u64, 2, 7, 1),
+            (BinOpTag::XOR as u64, 6, 3, 5),
+        ]);
+        let prover = MockProver::run(14, &circuit, circuit.instance()).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn circuit_test_expected_res() {
+        let circuit = BinOpCircuit::<Fr>::new(BinOpTag::ADD as u64, 1, 2, 3)
+            .expect_res(0, Encoded::from_unsigned(3));
+        let prover = MockProver::run(14, &circuit, circuit.instance()).unwrap();
+        prover.assert_satisfied();
+
+        // (ADD, 1, 2, 3) is in the table, but does not match the bound result
+        let table = BinopLookupMap::from_rows(generate_arith_table(BIT_WIDTH));
+        assert!(table.contains_row([BinOpTag::ADD as u64, 1, 2, 3]));
+        let circuit = BinOpCircuit::<Fr>::new(BinOpTag::ADD as u64, 1, 2, 3)
+            .expect_res(0, Encoded::from_unsigned(4));
+        let prover = MockProver::run(14, &circuit, circuit.instance()).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn binop_table_signed() {
+        let table = generate_binop_table(4, 4);
+        // 7 < 8 unsigned, but 8 is -8 as a 4-bit signed word
+        assert!(table.contains(&[BinOpTag::LT as u64, 7, 8, 1]));
+        assert!(table.contains(&[BinOpTag::SLT as u64, 7, 8, 0]));
+        assert!(table.contains(&[BinOpTag::SLT as u64, 8, 7, 1]));
+        assert!(table.contains(&[BinOpTag::SGT as u64, 7, 15, 1]));
+        assert!(table.contains(&[BinOpTag::SSUB as u64, 0, 1, (-1i64) as u64]));
+        assert_eq!(
+            encode_binop_res::<Fr>(BinOpTag::SSUB, (-1i64) as u64),
+            -Fr::from(1)
+        );
+
+        let circuit =
+            BinOpCircuit::<Fr>::from_ops(vec![(BinOpTag::SSUB as u64, 0, 1, (-1i64) as u64)]);
+        let prover = MockProver::run(14, &circuit, circuit.instance()).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn binop_table_wraps() {
+        let table = BinopLookupMap::new(4);
+        assert!(table.contains_row([BinOpTag::ADD as u64, 15, 15, 14]));
+        assert!(!table.contains_row([BinOpTag::ADD as u64, 15, 15, 30]));
+        assert!(table.contains_row([BinOpTag::MUL as u64, 15, 15, 1]));
+        assert!(table.contains_row([BinOpTag::SUB as u64, 0, 1, 15]));
+    }
+
+    #[test]
+    fn binop_table_shifts() {
+        let table = generate_binop_table(4, 4);
+        assert!(table.contains(&[BinOpTag::SHL as u64, 1, 3, 8]));
+        assert!(table.contains(&[BinOpTag::SHR as u64, 8, 2, 2]));
+        assert!(table.contains(&[BinOpTag::SHL as u64, 15, 1, 14]));
+        assert!(table.contains(&[BinOpTag::SHL as u64, 1, 4, 0]));
+        assert!(table.contains(&[BinOpTag::SHR as u64, 8, 4, 0]));
+    }
+}
@@ -0,0 +1,297 @@
+use std::marker::PhantomData;
+
+use super::generate_wide_binop_table_for;
+use super::mask;
+use super::padded_len;
+use super::BinOpTag;
+use super::BinaryOperationTable;
+use super::LookupTable;
+use super::{SubCircuit, SubCircuitConfig};
+use crate::backend::halo2::utils::Expr;
+use halo2_proofs::{
+    circuit::{Layouter, Region, SimpleFloorPlanner, Value},
+    halo2curves::FieldExt,
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+/// Width of a limb, the operand width of the underlying binop table.
+const LIMB_BITS: u32 = 8;
+
+/// Ops supported on 16-bit operands.
+const OPS: [BinOpTag; 2] = [BinOpTag::ADD, BinOpTag::MUL];
+
+/// Binops on 16-bit operands, each split into a low and a high 8-bit limb.
+///
+/// Every pair of limbs is looked up in a byte-wide binop table, and the
+/// partial results are composed back by a gate:
+/// - `ADD`: `res = ll + 2^8 * hh`,
+/// - `MUL`: `res = ll + 2^8 * (lh + hl) + 2^16 * hh`,
+///
+/// where `lh` is the partial result of the low `lhs` limb and the high `rhs`
+/// limb. The limbs are looked up in the exact rows of
+/// `generate_wide_binop_table_for`, so the carry out of the low limbs is
+/// carried by `ll` itself and needs no lookup of its own. Results are exact
+/// as well: `0xffff + 1` is `0x10000`.
+#[derive(Clone, Debug)]
+pub struct BinOp16Config<F: FieldExt> {
+    binop_table: BinaryOperationTable,
+    tag_column: Column<Advice>,
+    lhs_column: Column<Advice>,
+    rhs_column: Column<Advice>,
+    res_column: Column<Advice>,
+    /// `[lo, hi]` limbs of `lhs`.
+    lhs_limbs: [Column<Advice>; 2],
+    /// `[lo, hi]` limbs of `rhs`.
+    rhs_limbs: [Column<Advice>; 2],
+    /// Partial results `[ll, lh, hl, hh]`.
+    partials: [Column<Advice>; 4],
+    /// Enabled on every operation row. The limb lookup inputs are multiplied
+    /// by it, so the other rows are not looked up at all.
+    q_binop: Selector,
+    _marker: PhantomData<F>,
+}
+
+pub struct BinOp16ConfigArgs {
+    pub binop_table: BinaryOperationTable,
+}
+
+impl<F: FieldExt> SubCircuitConfig<F> for BinOp16Config<F> {
+    type ConfigArgs = BinOp16ConfigArgs;
+
+    fn new(
+        meta: &mut ConstraintSystem<F>,
+        BinOp16ConfigArgs { binop_table }: Self::ConfigArgs,
+    ) -> Self {
+        let tag_column = meta.advice_column();
+        let lhs_column = meta.advice_column();
+        let rhs_column = meta.advice_column();
+        let res_column = meta.advice_column();
+        let lhs_limbs = [(); 2].map(|_| meta.advice_column());
+        let rhs_limbs = [(); 2].map(|_| meta.advice_column());
+        let partials = [(); 4].map(|_| meta.advice_column());
+        let q_binop = meta.complex_selector();
+
+        binop_table.annotate_columns(meta);
+
+        let limb_pairs = [(0, 0), (0, 1), (1, 0), (1, 1)];
+        for ((l, r), partial) in limb_pairs.into_iter().zip(partials) {
+            meta.lookup_any("binop16_limb_lookup", |meta| {
+                let q_binop = meta.query_selector(q_binop);
+                let tag_value = meta.query_advice(tag_column, Rotation::cur());
+                let lhs_value = meta.query_advice(lhs_limbs[l], Rotation::cur());
+                let rhs_value = meta.query_advice(rhs_limbs[r], Rotation::cur());
+                let res_value = meta.query_advice(partial, Rotation::cur());
+                // Neither ADD nor MUL can trap.
+                let is_error_value = Expression::Constant(F::zero());
+
+                // The carry column is not looked up: the partial results
+                // are exact, so they already hold the carries.
+                let inputs = [tag_value, lhs_value, rhs_value, res_value, is_error_value];
+                inputs
+                    .into_iter()
+                    .map(|input| q_binop.clone() * input)
+                    .zip(binop_table.table_exprs(meta))
+                    .collect()
+            });
+        }
+
+        meta.create_gate("binop16 limb composition", |meta| {
+            let q_binop = meta.query_selector(q_binop);
+            let limb = Expression::Constant(F::from(1u64 << LIMB_BITS));
+            let tag = meta.query_advice(tag_column, Rotation::cur());
+            let lhs = meta.query_advice(lhs_column, Rotation::cur());
+            let rhs = meta.query_advice(rhs_column, Rotation::cur());
+            let res = meta.query_advice(res_column, Rotation::cur());
+            let [lhs_lo, lhs_hi] = lhs_limbs.map(|c| meta.query_advice(c, Rotation::cur()));
+            let [rhs_lo, rhs_hi] = rhs_limbs.map(|c| meta.query_advice(c, Rotation::cur()));
+            let [ll, lh, hl, hh] = partials.map(|c| meta.query_advice(c, Rotation::cur()));
+
+            let add: Expression<F> = BinOpTag::ADD.expr();
+            let mul: Expression<F> = BinOpTag::MUL.expr();
+            // `MUL` is `ADD + 1`, so once the tag is one of the two these are
+            // 1 for the op they name and 0 for the other one.
+            let is_add = mul.clone() - tag.clone();
+            let is_mul = tag.clone() - add.clone();
+            let add_res = ll.clone() + limb.clone() * hh.clone();
+            let mul_res = ll + limb.clone() * (lh + hl) + limb.clone() * limb.clone() * hh;
+
+            vec![
+                q_binop.clone() * (tag.clone() - add) * (tag - mul),
+                q_binop.clone() * (lhs - (lhs_lo + limb.clone() * lhs_hi)),
+                q_binop.clone() * (rhs - (rhs_lo + limb * rhs_hi)),
+                q_binop * (is_add * (res.clone() - add_res) + is_mul * (res - mul_res)),
+            ]
+        });
+
+        Self {
+            binop_table,
+            tag_column,
+            lhs_column,
+            rhs_column,
+            res_column,
+            lhs_limbs,
+            rhs_limbs,
+            partials,
+            q_binop,
+            _marker: PhantomData::default(),
+        }
+    }
+}
+
+impl<F: FieldExt> BinOp16Config<F> {
+    pub fn load_binop_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        let precomputed_binop = generate_wide_binop_table_for(&OPS, LIMB_BITS);
+        self.binop_table.load(layouter, precomputed_binop)?;
+        Ok(())
+    }
+
+    /// Assigns `op` on the 16-bit `lhs` and `rhs` at `offset`, claiming it
+    /// evaluates to `res`.
+    pub fn load_binop16_row(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        op: BinOpTag,
+        lhs: u64,
+        rhs: u64,
+        res: Value<F>,
+    ) -> Result<(), Error> {
+        if !OPS.contains(&op) {
+            return Err(Error::Synthesis);
+        }
+        let mask = mask(LIMB_BITS);
+        let lhs_limbs = [lhs & mask, (lhs >> LIMB_BITS) & mask];
+        let rhs_limbs = [rhs & mask, (rhs >> LIMB_BITS) & mask];
+        let partial = |l: u64, r: u64| op.eval_wide(l, r, LIMB_BITS);
+        let partials = [
+            partial(lhs_limbs[0], rhs_limbs[0]),
+            partial(lhs_limbs[0], rhs_limbs[1]),
+            partial(lhs_limbs[1], rhs_limbs[0]),
+            partial(lhs_limbs[1], rhs_limbs[1]),
+        ];
+
+        self.q_binop.enable(region, offset)?;
+        let known = |v: u64| Value::known(F::from(v));
+        region.assign_advice(|| "tag", self.tag_column, offset, || known(op as u64))?;
+        region.assign_advice(|| "lhs", self.lhs_column, offset, || known(lhs))?;
+        region.assign_advice(|| "rhs", self.rhs_column, offset, || known(rhs))?;
+        region.assign_advice(|| "res", self.res_column, offset, || res)?;
+        for (column, limb) in self.lhs_limbs.iter().zip(lhs_limbs) {
+            region.assign_advice(|| "lhs limb", *column, offset, || known(limb))?;
+        }
+        for (column, limb) in self.rhs_limbs.iter().zip(rhs_limbs) {
+            region.assign_advice(|| "rhs limb", *column, offset, || known(limb))?;
+        }
+        for (column, partial) in self.partials.iter().zip(partials) {
+            region.assign_advice(|| "partial res", *column, offset, || known(partial))?;
+        }
+        Ok(())
+    }
+}
+
+/// Witnesses one `(op, lhs, rhs, res)` operation on 16-bit operands per row.
+#[derive(Clone, Debug)]
+pub struct BinOp16Circuit<F: FieldExt> {
+    ops: Vec<(BinOpTag, u64, u64, u64)>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> BinOp16Circuit<F> {
+    pub fn new(ops: Vec<(BinOpTag, u64, u64, u64)>) -> Self {
+        Self {
+            ops,
+            _marker: PhantomData::default(),
+        }
+    }
+}
+
+impl<F: FieldExt> SubCircuit<F> for BinOp16Circuit<F> {
+    type Config = BinOp16Config<F>;
+
+    fn min_num_rows(&self) -> usize {
+        padded_len(generate_wide_binop_table_for(&OPS, LIMB_BITS).len()) + self.ops.len()
+    }
+
+    fn synthesize_sub(
+        &self,
+        config: &Self::Config,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        config.load_binop_table(layouter)?;
+        layouter.assign_region(
+            || "binop16",
+            |mut region| {
+                for (offset, (op, lhs, rhs, res)) in self.ops.iter().enumerate() {
+                    let res = Value::known(F::from(*res));
+                    config.load_binop16_row(&mut region, offset, *op, *lhs, *rhs, res)?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for BinOp16Circuit<F> {
+    type Config = BinOp16Config<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(vec![(BinOpTag::ADD, 0, 0, 0); self.ops.len()])
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let binop_table = BinaryOperationTable::construct(meta, LIMB_BITS);
+        BinOp16Config::new(meta, BinOp16ConfigArgs { binop_table })
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        self.synthesize_sub(&config, &mut layouter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+
+    use super::*;
+
+    /// The ADD and MUL rows at byte width need 2^17 rows, and one more once
+    /// padded.
+    const K: u32 = 18;
+
+    #[test]
+    fn add_carries_across_limbs() {
+        let circuit = BinOp16Circuit::<Fr>::new(vec![(BinOpTag::ADD, 0x00ff, 0x0001, 0x0100)]);
+        let prover = MockProver::run(K, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+
+        let circuit = BinOp16Circuit::<Fr>::new(vec![(BinOpTag::ADD, 0x00ff, 0x0001, 0x0000)]);
+        let prover = MockProver::run(K, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn mul_16_bit() {
+        let circuit = BinOp16Circuit::<Fr>::new(vec![
+            (BinOpTag::MUL, 300, 500, 150_000),
+            (BinOpTag::MUL, 0xffff, 0xffff, 0xfffe_0001),
+        ]);
+        let prover = MockProver::run(K, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+
+        let circuit = BinOp16Circuit::<Fr>::new(vec![(BinOpTag::MUL, 300, 500, 150_001)]);
+        let prover = MockProver::run(K, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn unsupported_op_fails_to_synthesize() {
+        let circuit = BinOp16Circuit::<Fr>::new(vec![(BinOpTag::SUB, 500, 300, 200)]);
+        assert!(MockProver::run(K, &circuit, vec![]).is_err());
+    }
+}
@@ -1 +1,392 @@
+use std::marker::PhantomData;
 
+use super::CallTable;
+use super::LookupTable;
+use super::{SubCircuit, SubCircuitConfig};
+use halo2_proofs::{
+    circuit::{Layouter, Region, SimpleFloorPlanner, Value},
+    halo2curves::FieldExt,
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+/// Tracks the call stack depth over a sequence of calls and returns.
+///
+/// Row `i` holds the depth before the `i`-th event, which starts at 0, and
+/// `top`, the time of the innermost call that has not returned. A call must
+/// be an edge of `call_table`, increments the depth, makes its time the new
+/// top and records the `top` it covers in `prev_top`. A return is only
+/// allowed above depth 0 and decrements the depth; it looks up
+/// `(top, to, prev_top)` among the calls as `(time, from + 1, prev_top)` and
+/// restores `prev_top` as the new top.
+///
+/// As in `StackConfig`, the calls thus form a linked list in time order, so
+/// a return goes back to `from + 1` of the innermost pending call, not of
+/// any call that entered the same depth. The time of row `i` is `i + 1`, and
+/// the top outside of any call is 0.
+#[derive(Clone, Debug)]
+pub struct CallConfig<F: FieldExt> {
+    call_table: CallTable,
+    /// Row index of the event in the region, plus 1.
+    time: Column<Fixed>,
+    depth: Column<Advice>,
+    /// Inverse of `depth`, only constrained on returns.
+    depth_inv: Column<Advice>,
+    /// Time of the innermost pending call before the event.
+    top: Column<Advice>,
+    /// On a call, the top it covers. On a return, the top it restores.
+    prev_top: Column<Advice>,
+    from: Column<Advice>,
+    to: Column<Advice>,
+    /// Enabled on the first row.
+    q_first: Selector,
+    /// Enabled on calls.
+    q_call: Selector,
+    /// Enabled on returns.
+    q_ret: Selector,
+    _marker: PhantomData<F>,
+}
+
+pub struct CallConfigArgs {
+    pub call_table: CallTable,
+}
+
+impl<F: FieldExt> SubCircuitConfig<F> for CallConfig<F> {
+    type ConfigArgs = CallConfigArgs;
+
+    fn new(
+        meta: &mut ConstraintSystem<F>,
+        CallConfigArgs { call_table }: Self::ConfigArgs,
+    ) -> Self {
+        let time = meta.fixed_column();
+        let depth = meta.advice_column();
+        let depth_inv = meta.advice_column();
+        let top = meta.advice_column();
+        let prev_top = meta.advice_column();
+        let from = meta.advice_column();
+        let to = meta.advice_column();
+        let q_first = meta.selector();
+        let q_call = meta.complex_selector();
+        let q_ret = meta.complex_selector();
+
+        call_table.annotate_columns(meta);
+
+        meta.create_gate("call depth", |meta| {
+            let one = Expression::Constant(F::one());
+            let q_first = meta.query_selector(q_first);
+            let q_call = meta.query_selector(q_call);
+            let q_ret = meta.query_selector(q_ret);
+            let depth_cur = meta.query_advice(depth, Rotation::cur());
+            let depth_next = meta.query_advice(depth, Rotation::next());
+            let depth_inv = meta.query_advice(depth_inv, Rotation::cur());
+
+            vec![
+                q_first * depth_cur.clone(),
+                q_call * (depth_next.clone() - depth_cur.clone() - one.clone()),
+                q_ret.clone() * (depth_next - depth_cur.clone() + one.clone()),
+                q_ret * (depth_cur * depth_inv - one),
+            ]
+        });
+
+        meta.create_gate("call top", |meta| {
+            let q_first = meta.query_selector(q_first);
+            let q_call = meta.query_selector(q_call);
+            let q_ret = meta.query_selector(q_ret);
+            let time = meta.query_fixed(time, Rotation::cur());
+            let top_cur = meta.query_advice(top, Rotation::cur());
+            let top_next = meta.query_advice(top, Rotation::next());
+            let prev_top = meta.query_advice(prev_top, Rotation::cur());
+
+            vec![
+                q_first * top_cur.clone(),
+                q_call.clone() * (top_next.clone() - time),
+                q_call * (prev_top.clone() - top_cur),
+                q_ret * (top_next - prev_top),
+            ]
+        });
+
+        meta.lookup_any("call_lookup", |meta| {
+            let q_call = meta.query_selector(q_call);
+            let inputs = [from, to]
+                .map(|column| q_call.clone() * meta.query_advice(column, Rotation::cur()));
+            inputs
+                .into_iter()
+                .zip(call_table.table_exprs(meta))
+                .collect()
+        });
+
+        meta.lookup_any("ret_lookup", |meta| {
+            let one = Expression::Constant(F::one());
+            let q_call = meta.query_selector(q_call);
+            let q_ret = meta.query_selector(q_ret);
+            let time = meta.query_fixed(time, Rotation::cur());
+            let top = meta.query_advice(top, Rotation::cur());
+            let prev_top = meta.query_advice(prev_top, Rotation::cur());
+            let from = meta.query_advice(from, Rotation::cur());
+            let to = meta.query_advice(to, Rotation::cur());
+
+            vec![
+                (q_ret.clone() * top, q_call.clone() * time),
+                (q_ret.clone() * to, q_call.clone() * (from + one)),
+                (q_ret * prev_top.clone(), q_call * prev_top),
+            ]
+        });
+
+        Self {
+            call_table,
+            time,
+            depth,
+            depth_inv,
+            top,
+            prev_top,
+            from,
+            to,
+            q_first,
+            q_call,
+            q_ret,
+            _marker: PhantomData::default(),
+        }
+    }
+}
+
+impl<F: FieldExt> CallConfig<F> {
+    pub fn load_call_table(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        calls: Vec<[u64; 2]>,
+    ) -> Result<(), Error> {
+        self.call_table.load(layouter, calls)?;
+        Ok(())
+    }
+
+    /// Assigns the depth and top before the event at `offset`, or after the
+    /// last one.
+    pub fn assign_state(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        depth: Value<F>,
+        top: Value<F>,
+    ) -> Result<(), Error> {
+        if offset == 0 {
+            self.q_first.enable(region, offset)?;
+        }
+        let depth_inv = depth.map(|depth| depth.invert().unwrap_or(F::zero()));
+        let time = Value::known(F::from(offset as u64 + 1));
+        region.assign_fixed(|| "time", self.time, offset, || time)?;
+        region.assign_advice(|| "depth", self.depth, offset, || depth)?;
+        region.assign_advice(|| "depth inv", self.depth_inv, offset, || depth_inv)?;
+        region.assign_advice(|| "top", self.top, offset, || top)?;
+        Ok(())
+    }
+
+    /// Assigns a call from `from` to `to` at `offset`, covering `top`.
+    pub fn assign_call(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        from: Value<F>,
+        to: Value<F>,
+        top: Value<F>,
+    ) -> Result<(), Error> {
+        self.q_call.enable(region, offset)?;
+        region.assign_advice(|| "from", self.from, offset, || from)?;
+        region.assign_advice(|| "to", self.to, offset, || to)?;
+        region.assign_advice(|| "prev top", self.prev_top, offset, || top)?;
+        Ok(())
+    }
+
+    /// Assigns a return to `to` at `offset`, restoring `prev_top`, the top
+    /// covered by the call it returns from.
+    pub fn assign_ret(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        to: Value<F>,
+        prev_top: Value<F>,
+    ) -> Result<(), Error> {
+        self.q_ret.enable(region, offset)?;
+        region.assign_advice(|| "to", self.to, offset, || to)?;
+        region.assign_advice(|| "prev top", self.prev_top, offset, || prev_top)?;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum CallEvent {
+    Call { from: u64, to: u64 },
+    Ret { to: u64 },
+}
+
+/// Witnesses a sequence of calls and returns against the `(from, to)` call
+/// edges of the program.
+#[derive(Clone, Debug)]
+pub struct CallCircuit<F: FieldExt> {
+    calls: Vec<[u64; 2]>,
+    events: Vec<CallEvent>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> CallCircuit<F> {
+    pub fn new(calls: Vec<[u64; 2]>, events: Vec<CallEvent>) -> Self {
+        Self {
+            calls,
+            events,
+            _marker: PhantomData::default(),
+        }
+    }
+}
+
+impl<F: FieldExt> SubCircuit<F> for CallCircuit<F> {
+    type Config = CallConfig<F>;
+
+    fn min_num_rows(&self) -> usize {
+        self.calls.len().max(self.events.len() + 1)
+    }
+
+    fn synthesize_sub(
+        &self,
+        config: &Self::Config,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        config.load_call_table(layouter, self.calls.clone())?;
+        layouter.assign_region(
+            || "call",
+            |mut region| {
+                // Signed, so that a return at depth 0 is witnessed as -1 and
+                // rejected by the circuit rather than here.
+                let mut depth = 0i64;
+                // `prev_top` of the pending calls.
+                let mut calls: Vec<u64> = vec![];
+                let mut top = 0;
+                for (offset, event) in self.events.iter().enumerate() {
+                    let state = [encode_depth(depth), F::from(top)].map(Value::known);
+                    config.assign_state(&mut region, offset, state[0], state[1])?;
+                    match *event {
+                        CallEvent::Call { from, to } => {
+                            let [from, to, prev] =
+                                [from, to, top].map(|v| Value::known(F::from(v)));
+                            config.assign_call(&mut region, offset, from, to, prev)?;
+                            calls.push(top);
+                            top = offset as u64 + 1;
+                            depth += 1;
+                        }
+                        CallEvent::Ret { to } => {
+                            let prev_top = calls.pop().unwrap_or(0);
+                            let [to, prev] = [to, prev_top].map(|v| Value::known(F::from(v)));
+                            config.assign_ret(&mut region, offset, to, prev)?;
+                            top = prev_top;
+                            depth -= 1;
+                        }
+                    }
+                }
+                let state = [encode_depth(depth), F::from(top)].map(Value::known);
+                config.assign_state(&mut region, self.events.len(), state[0], state[1])
+            },
+        )
+    }
+}
+
+fn encode_depth<F: FieldExt>(depth: i64) -> F {
+    if depth < 0 {
+        -F::from(depth.unsigned_abs())
+    } else {
+        F::from(depth as u64)
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for CallCircuit<F> {
+    type Config = CallConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    /// Keeps the kind of every event, which decides the selectors, but
+    /// not its addresses.
+    fn without_witnesses(&self) -> Self {
+        let events = self
+            .events
+            .iter()
+            .map(|event| match event {
+                CallEvent::Call { .. } => CallEvent::Call { from: 0, to: 0 },
+                CallEvent::Ret { .. } => CallEvent::Ret { to: 0 },
+            })
+            .collect();
+        Self::new(self.calls.clone(), events)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let call_table = CallTable::construct(meta);
+        CallConfig::new(meta, CallConfigArgs { call_table })
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        self.synthesize_sub(&config, &mut layouter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+
+    use super::*;
+
+    fn run(events: Vec<CallEvent>) -> MockProver<Fr> {
+        let circuit = CallCircuit::<Fr>::new(vec![[1, 10], [12, 20]], events);
+        MockProver::run(5, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn nested_call_and_return() {
+        let events = vec![
+            CallEvent::Call { from: 1, to: 10 },
+            CallEvent::Call { from: 12, to: 20 },
+            CallEvent::Ret { to: 13 },
+            CallEvent::Ret { to: 2 },
+        ];
+        run(events).assert_satisfied();
+    }
+
+    #[test]
+    fn return_to_wrong_address_fails() {
+        let events = vec![
+            CallEvent::Call { from: 1, to: 10 },
+            CallEvent::Call { from: 12, to: 20 },
+            CallEvent::Ret { to: 2 },
+        ];
+        assert!(run(events).verify().is_err());
+    }
+
+    #[test]
+    fn return_to_finished_call_fails() {
+        // the second return is at the depth of the first call, but goes
+        // back to the call from 12, which is still pending
+        let events = vec![
+            CallEvent::Call { from: 1, to: 10 },
+            CallEvent::Ret { to: 2 },
+            CallEvent::Call { from: 12, to: 20 },
+            CallEvent::Ret { to: 2 },
+        ];
+        assert!(run(events).verify().is_err());
+
+        let events = vec![
+            CallEvent::Call { from: 1, to: 10 },
+            CallEvent::Ret { to: 2 },
+            CallEvent::Call { from: 12, to: 20 },
+            CallEvent::Ret { to: 13 },
+        ];
+        run(events).assert_satisfied();
+    }
+
+    #[test]
+    fn negative_depth_fails() {
+        let events = vec![
+            CallEvent::Call { from: 1, to: 10 },
+            CallEvent::Ret { to: 2 },
+            CallEvent::Ret { to: 2 },
+        ];
+        assert!(run(events).verify().is_err());
+    }
+}
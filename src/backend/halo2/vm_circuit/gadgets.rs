@@ -0,0 +1,192 @@
+use halo2_proofs::{
+    circuit::{Region, Value},
+    halo2curves::FieldExt,
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, VirtualCells},
+    poly::Rotation,
+};
+
+/// Whether an expression is zero, through the inverse hint `inv`:
+/// `value * (1 - value * inv) = 0` and `is_zero = 1 - value * inv`.
+///
+/// If `value` is not zero the first constraint forces `inv` to be its
+/// inverse and `is_zero` to 0. If it is zero `is_zero` is 1 whatever `inv`.
+#[derive(Clone, Debug)]
+pub struct IsZeroGadget<F: FieldExt> {
+    value_inv: Column<Advice>,
+    is_zero: Expression<F>,
+}
+
+impl<F: FieldExt> IsZeroGadget<F> {
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        value: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+    ) -> Self {
+        let value_inv = meta.advice_column();
+        let mut is_zero = Expression::Constant(F::zero());
+
+        meta.create_gate("is zero", |meta| {
+            let one = Expression::Constant(F::one());
+            let q_enable = q_enable(meta);
+            let value = value(meta);
+            let inv = meta.query_advice(value_inv, Rotation::cur());
+
+            is_zero = one - value.clone() * inv;
+            vec![q_enable * value * is_zero.clone()]
+        });
+
+        Self { value_inv, is_zero }
+    }
+
+    /// 1 if the value is zero, 0 otherwise, on rows where the gadget is
+    /// enabled.
+    pub fn expr(&self) -> Expression<F> {
+        self.is_zero.clone()
+    }
+
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        value: Value<F>,
+    ) -> Result<(), Error> {
+        let inv = value.map(|value| value.invert().unwrap_or(F::zero()));
+        region.assign_advice(|| "is zero inv", self.value_inv, offset, || inv)?;
+        Ok(())
+    }
+}
+
+/// Whether two expressions are equal, as an `IsZeroGadget` on their
+/// difference.
+#[derive(Clone, Debug)]
+pub struct IsEqualGadget<F: FieldExt> {
+    is_zero: IsZeroGadget<F>,
+}
+
+impl<F: FieldExt> IsEqualGadget<F> {
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        lhs: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        rhs: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+    ) -> Self {
+        let is_zero = IsZeroGadget::configure(meta, q_enable, |meta| lhs(meta) - rhs(meta));
+        Self { is_zero }
+    }
+
+    /// 1 if both values are equal, 0 otherwise, on rows where the gadget is
+    /// enabled.
+    pub fn expr(&self) -> Expression<F> {
+        self.is_zero.expr()
+    }
+
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        lhs: Value<F>,
+        rhs: Value<F>,
+    ) -> Result<(), Error> {
+        self.is_zero.assign(region, offset, lhs - rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, Selector},
+    };
+
+    use super::*;
+
+    /// Claims `is_eq` is whether `lhs` equals `rhs`.
+    #[derive(Clone, Debug, Default)]
+    struct IsEqualCircuit {
+        lhs: u64,
+        rhs: u64,
+        is_eq: u64,
+    }
+
+    #[derive(Clone, Debug)]
+    struct IsEqualConfig {
+        lhs: Column<Advice>,
+        rhs: Column<Advice>,
+        is_eq: Column<Advice>,
+        q_enable: Selector,
+        gadget: IsEqualGadget<Fr>,
+    }
+
+    impl Circuit<Fr> for IsEqualCircuit {
+        type Config = IsEqualConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let lhs = meta.advice_column();
+            let rhs = meta.advice_column();
+            let is_eq = meta.advice_column();
+            let q_enable = meta.selector();
+            let gadget = IsEqualGadget::configure(
+                meta,
+                |meta| meta.query_selector(q_enable),
+                |meta| meta.query_advice(lhs, Rotation::cur()),
+                |meta| meta.query_advice(rhs, Rotation::cur()),
+            );
+            meta.create_gate("is equal", |meta| {
+                let q_enable = meta.query_selector(q_enable);
+                let is_eq = meta.query_advice(is_eq, Rotation::cur());
+                vec![q_enable * (is_eq - gadget.expr())]
+            });
+            IsEqualConfig {
+                lhs,
+                rhs,
+                is_eq,
+                q_enable,
+                gadget,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "is equal",
+                |mut region| {
+                    let [lhs, rhs, is_eq] =
+                        [self.lhs, self.rhs, self.is_eq].map(|v| Value::known(Fr::from(v)));
+                    config.q_enable.enable(&mut region, 0)?;
+                    region.assign_advice(|| "lhs", config.lhs, 0, || lhs)?;
+                    region.assign_advice(|| "rhs", config.rhs, 0, || rhs)?;
+                    region.assign_advice(|| "is eq", config.is_eq, 0, || is_eq)?;
+                    config.gadget.assign(&mut region, 0, lhs, rhs)
+                },
+            )
+        }
+    }
+
+    fn verify(lhs: u64, rhs: u64, is_eq: u64) -> bool {
+        let circuit = IsEqualCircuit { lhs, rhs, is_eq };
+        let prover = MockProver::run(4, &circuit, vec![]).unwrap();
+        prover.verify().is_ok()
+    }
+
+    #[test]
+    fn is_equal_on_equal_inputs() {
+        assert!(verify(7, 7, 1));
+        assert!(!verify(7, 7, 0));
+    }
+
+    #[test]
+    fn is_equal_on_unequal_inputs() {
+        assert!(verify(7, 3, 0));
+        assert!(!verify(7, 3, 1));
+    }
+}
@@ -0,0 +1,63 @@
+use std::io;
+
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::VerifyingKey,
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+    SerdeFormat,
+};
+
+use super::binop::BinOpCircuit;
+
+/// Writes the verifying key of a `BinOpCircuit`.
+///
+/// Only the commitments and `k` are written: the constraint system is
+/// rebuilt from `BinOpCircuit::configure` by `read_vk`.
+pub fn write_vk(vk: &VerifyingKey<G1Affine>, writer: &mut impl io::Write) -> io::Result<()> {
+    vk.write(writer, SerdeFormat::RawBytes)
+}
+
+/// Reads a verifying key written by `write_vk`, checking that it was built
+/// for the size of `params`.
+pub fn read_vk(
+    reader: &mut impl io::Read,
+    params: &ParamsKZG<Bn256>,
+) -> io::Result<VerifyingKey<G1Affine>> {
+    let vk = VerifyingKey::read::<_, BinOpCircuit<Fr>>(reader, SerdeFormat::RawBytes)?;
+    if vk.get_domain().k() != params.k() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "verifying key is for k = {}, but the params are for k = {}",
+                vk.get_domain().k(),
+                params.k()
+            ),
+        ));
+    }
+    Ok(vk)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::halo2::table::BinOpTag;
+    use crate::backend::halo2::utils::SubCircuit;
+    use crate::backend::halo2::vm_circuit::prover::{verify_with_vk, BinOpProver};
+
+    #[test]
+    fn vk_round_trip() {
+        let params = ParamsKZG::<Bn256>::setup(14, ark_std::test_rng());
+        let prover = BinOpProver::new(&params, 1).unwrap();
+        let circuit = BinOpCircuit::new(BinOpTag::ADD as u64, 1, 2, 3);
+        let public_inputs = circuit.instance();
+        let proof = prover.prove(circuit, ark_std::test_rng()).unwrap();
+
+        let mut bytes = vec![];
+        write_vk(prover.vk(), &mut bytes).unwrap();
+        let vk = read_vk(&mut bytes.as_slice(), prover.params()).unwrap();
+        assert!(verify_with_vk(prover.params(), &vk, &proof, &public_inputs));
+
+        let other_params = ParamsKZG::<Bn256>::setup(13, ark_std::test_rng());
+        assert!(read_vk(&mut bytes.as_slice(), &other_params).is_err());
+    }
+}
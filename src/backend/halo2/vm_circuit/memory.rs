@@ -0,0 +1,394 @@
+use std::marker::PhantomData;
+
+use super::LookupTable;
+use super::MemoryTable;
+use super::RangeCheckTable;
+use super::{SubCircuit, SubCircuitConfig};
+use halo2_proofs::{
+    circuit::{Layouter, Region, SimpleFloorPlanner, Value},
+    halo2curves::FieldExt,
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+/// Width of memory addresses. The gap between two neighbouring sorted
+/// accesses is range checked to it: their addresses, or the times of two
+/// accesses to the same address, must be less than `2^ADDRESS_BITS` apart.
+const ADDRESS_BITS: u32 = 32;
+
+/// Bits of the limbs the gap is range checked in, i.e. of the range table.
+const LIMB_BITS: u32 = 8;
+
+const NUM_LIMBS: usize = (ADDRESS_BITS / LIMB_BITS) as usize;
+
+/// Checks read-after-write consistency of the memory accesses in
+/// `memory_table`.
+///
+/// The accesses are given in execution order, the access of row `i` at time
+/// `i + 1`. A sorted copy of them, by address and then by time, is assigned
+/// next to them, so that every read either follows an access to the same
+/// address and returns its value, or is the first access to its address and
+/// returns 0.
+///
+/// The copy is tied to the accesses in two steps. The gap between
+/// neighbouring sorted rows, in address or else in time, is range checked,
+/// so that the rows are strictly sorted and thus distinct. Every sorted row
+/// is then looked up among the accesses, whose times are distinct too: as
+/// both hold as many rows, the copy is a permutation of the accesses.
+#[derive(Clone, Debug)]
+pub struct MemoryConfig<F: FieldExt> {
+    memory_table: MemoryTable,
+    /// Row index of the access in execution order, plus 1.
+    time: Column<Fixed>,
+    /// The accesses sorted by address and then by time.
+    sorted: MemoryTable,
+    /// Time of the sorted access.
+    sorted_time: Column<Advice>,
+    /// Inverse of `address - address_prev` in the sorted rows, 0 when both
+    /// are equal.
+    addr_diff_inv: Column<Advice>,
+    /// `address - address_prev - 1` between sorted rows of different
+    /// addresses, `time - time_prev - 1` otherwise, in `LIMB_BITS`-bit limbs
+    /// from the least significant one.
+    order_diff: [Column<Advice>; NUM_LIMBS],
+    range_table: RangeCheckTable,
+    /// Enabled on every access row, of both the accesses and their copy.
+    q_mem: Selector,
+    /// Enabled on the first access row.
+    q_first: Selector,
+    /// Enabled on every access row but the first one.
+    q_step: Selector,
+    _marker: PhantomData<F>,
+}
+
+pub struct MemoryConfigArgs {
+    pub memory_table: MemoryTable,
+}
+
+impl<F: FieldExt> SubCircuitConfig<F> for MemoryConfig<F> {
+    type ConfigArgs = MemoryConfigArgs;
+
+    fn new(
+        meta: &mut ConstraintSystem<F>,
+        MemoryConfigArgs { memory_table }: Self::ConfigArgs,
+    ) -> Self {
+        let time = meta.fixed_column();
+        let sorted = MemoryTable::construct(meta);
+        let sorted_time = meta.advice_column();
+        let addr_diff_inv = meta.advice_column();
+        let order_diff = [(); NUM_LIMBS].map(|_| meta.advice_column());
+        let range_table = RangeCheckTable::construct(meta, LIMB_BITS);
+        let q_mem = meta.complex_selector();
+        let q_first = meta.selector();
+        let q_step = meta.complex_selector();
+
+        memory_table.annotate_columns(meta);
+        range_table.annotate_columns(meta);
+
+        meta.create_gate("memory access", |meta| {
+            let one = Expression::Constant(F::one());
+            let q_mem = meta.query_selector(q_mem);
+            let q_first = meta.query_selector(q_first);
+            let value = meta.query_advice(sorted.value, Rotation::cur());
+            let is_write = meta.query_advice(sorted.is_write, Rotation::cur());
+            let is_read = one - is_write.clone();
+
+            vec![
+                q_mem * is_write * is_read.clone(),
+                q_first * is_read * value,
+            ]
+        });
+
+        // Only enabled from the second row on, so that `Rotation::prev` stays
+        // within the region.
+        meta.create_gate("memory consistency", |meta| {
+            let one = Expression::Constant(F::one());
+            let q_step = meta.query_selector(q_step);
+
+            let addr = meta.query_advice(sorted.address, Rotation::cur());
+            let addr_prev = meta.query_advice(sorted.address, Rotation::prev());
+            let time = meta.query_advice(sorted_time, Rotation::cur());
+            let time_prev = meta.query_advice(sorted_time, Rotation::prev());
+            let value = meta.query_advice(sorted.value, Rotation::cur());
+            let value_prev = meta.query_advice(sorted.value, Rotation::prev());
+            let is_write = meta.query_advice(sorted.is_write, Rotation::cur());
+            let inv = meta.query_advice(addr_diff_inv, Rotation::cur());
+            let limb_base = Expression::Constant(F::from(1 << LIMB_BITS));
+            let mut order_diff_expr = Expression::Constant(F::zero());
+            for limb in order_diff.iter().rev() {
+                order_diff_expr =
+                    order_diff_expr * limb_base.clone() + meta.query_advice(*limb, Rotation::cur());
+            }
+
+            let diff = addr - addr_prev;
+            let same_addr = one.clone() - diff.clone() * inv;
+            let is_read = one.clone() - is_write;
+            let gap = same_addr.clone() * (time - time_prev - one.clone())
+                + (one.clone() - same_addr.clone()) * (diff.clone() - one.clone());
+
+            vec![
+                q_step.clone() * diff * same_addr.clone(),
+                q_step.clone() * is_read.clone() * same_addr.clone() * (value.clone() - value_prev),
+                q_step.clone() * is_read * (one - same_addr) * value,
+                q_step * (order_diff_expr - gap),
+            ]
+        });
+
+        for limb in order_diff {
+            range_table.range_check(meta, "memory_order_range", |meta| {
+                let q_step = meta.query_selector(q_step);
+                q_step * meta.query_advice(limb, Rotation::cur())
+            });
+        }
+
+        // `q_mem` is part of the tuple, so that an enabled sorted row cannot
+        // match the zero tuple of a row that holds no access.
+        meta.lookup_any("memory_sorted_lookup", |meta| {
+            let q_mem = meta.query_selector(q_mem);
+            let sorted = [
+                meta.query_advice(sorted.address, Rotation::cur()),
+                meta.query_advice(sorted_time, Rotation::cur()),
+                meta.query_advice(sorted.value, Rotation::cur()),
+                meta.query_advice(sorted.is_write, Rotation::cur()),
+            ];
+            let accesses = [
+                meta.query_advice(memory_table.address, Rotation::cur()),
+                meta.query_fixed(time, Rotation::cur()),
+                meta.query_advice(memory_table.value, Rotation::cur()),
+                meta.query_advice(memory_table.is_write, Rotation::cur()),
+            ];
+            sorted
+                .into_iter()
+                .zip(accesses)
+                .map(|(input, table)| (q_mem.clone() * input, q_mem.clone() * table))
+                .chain([(q_mem.clone(), q_mem)])
+                .collect()
+        });
+
+        Self {
+            memory_table,
+            time,
+            sorted,
+            sorted_time,
+            addr_diff_inv,
+            order_diff,
+            range_table,
+            q_mem,
+            q_first,
+            q_step,
+            _marker: PhantomData::default(),
+        }
+    }
+}
+
+impl<F: FieldExt> MemoryConfig<F> {
+    pub fn load_range_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        self.range_table.load(layouter)?;
+        Ok(())
+    }
+
+    /// Assigns the access at `offset` in execution order, i.e. at time
+    /// `offset + 1`.
+    pub fn load_mem_op(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        addr: Value<F>,
+        value: Value<F>,
+        is_write: Value<F>,
+    ) -> Result<(), Error> {
+        self.memory_table
+            .assign(region, offset, [addr, value, is_write])?;
+        let time = Value::known(F::from(offset as u64 + 1));
+        region.assign_fixed(|| "time", self.time, offset, || time)?;
+        self.q_mem.enable(region, offset)?;
+        Ok(())
+    }
+
+    /// Assigns the `(address, time, value, is_write)` access at `offset` of
+    /// the sorted copy. `prev` is the `(address, time)` of the sorted access
+    /// at `offset - 1`, `None` for the first access.
+    pub fn load_sorted_op(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        [addr, time, value, is_write]: [Value<F>; 4],
+        prev: Option<(Value<F>, Value<F>)>,
+    ) -> Result<(), Error> {
+        self.sorted
+            .assign(region, offset, [addr, value, is_write])?;
+        region.assign_advice(|| "sorted time", self.sorted_time, offset, || time)?;
+        match prev {
+            Some((prev_addr, prev_time)) => {
+                self.q_step.enable(region, offset)?;
+                let diff = addr - prev_addr;
+                let inv = diff.map(|diff| diff.invert().unwrap_or(F::zero()));
+                let gap = diff.zip(time - prev_time).map(|(diff, time_diff)| {
+                    if diff == F::zero() {
+                        time_diff - F::one()
+                    } else {
+                        diff - F::one()
+                    }
+                });
+                region.assign_advice(|| "addr diff inv", self.addr_diff_inv, offset, || inv)?;
+                // A gap past `ADDRESS_BITS` bits keeps its low limbs only, which the
+                // circuit rejects.
+                let gap = gap.map(|gap| gap.get_lower_128());
+                for (i, limb) in self.order_diff.iter().enumerate() {
+                    let bits = gap.map(|gap| (gap >> (i as u32 * LIMB_BITS)) % (1 << LIMB_BITS));
+                    let bits = bits.map(|bits| F::from(bits as u64));
+                    region.assign_advice(|| "order diff", *limb, offset, || bits)?;
+                }
+            }
+            None => self.q_first.enable(region, offset)?,
+        }
+        Ok(())
+    }
+}
+
+/// Witnesses `(address, value, is_write)` memory accesses, given in
+/// execution order.
+#[derive(Clone, Debug)]
+pub struct MemoryCircuit<F: FieldExt> {
+    ops: Vec<[u64; 3]>,
+    /// `(address, time, value, is_write)` of the accesses, sorted by address
+    /// and then by time.
+    sorted: Vec<[u64; 4]>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> MemoryCircuit<F> {
+    pub fn new(ops: Vec<[u64; 3]>) -> Self {
+        let mut sorted = ops
+            .iter()
+            .zip(1..)
+            .map(|([addr, value, is_write], time)| [*addr, time, *value, *is_write])
+            .collect::<Vec<_>>();
+        sorted.sort_by_key(|[addr, time, ..]| (*addr, *time));
+        Self {
+            ops,
+            sorted,
+            _marker: PhantomData::default(),
+        }
+    }
+}
+
+impl<F: FieldExt> SubCircuit<F> for MemoryCircuit<F> {
+    type Config = MemoryConfig<F>;
+
+    fn synthesize_sub(
+        &self,
+        config: &Self::Config,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        config.load_range_table(layouter)?;
+        layouter.assign_region(
+            || "memory",
+            |mut region| {
+                for (offset, op) in self.ops.iter().enumerate() {
+                    let [addr, value, is_write] = op.map(|v| Value::known(F::from(v)));
+                    config.load_mem_op(&mut region, offset, addr, value, is_write)?;
+                }
+                let mut prev = None;
+                for (offset, op) in self.sorted.iter().enumerate() {
+                    let op = op.map(|v| Value::known(F::from(v)));
+                    config.load_sorted_op(&mut region, offset, op, prev)?;
+                    prev = Some((op[0], op[1]));
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for MemoryCircuit<F> {
+    type Config = MemoryConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(vec![[0; 3]; self.ops.len()])
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let memory_table = MemoryTable::construct(meta);
+        MemoryConfig::new(meta, MemoryConfigArgs { memory_table })
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        self.synthesize_sub(&config, &mut layouter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+
+    use super::*;
+
+    /// Fits the `2^LIMB_BITS` rows of the range table.
+    const K: u32 = 9;
+
+    #[test]
+    fn write_then_read() {
+        let k = K;
+        let circuit = MemoryCircuit::<Fr>::new(vec![[5, 42, 1], [3, 0, 0], [5, 42, 0]]);
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn distant_addresses() {
+        let circuit = MemoryCircuit::<Fr>::new(vec![[0, 7, 1], [1000, 8, 1], [0, 7, 0]]);
+        let prover = MockProver::run(K, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+
+        let far = 1 << ADDRESS_BITS;
+        let circuit = MemoryCircuit::<Fr>::new(vec![[0, 7, 1], [far, 8, 1]]);
+        let prover = MockProver::run(K, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+        let circuit = MemoryCircuit::<Fr>::new(vec![[0, 7, 1], [far + 1, 8, 1]]);
+        let prover = MockProver::run(K, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn stale_read_fails() {
+        let k = K;
+        let circuit = MemoryCircuit::<Fr>::new(vec![[5, 1, 1], [5, 2, 1], [5, 1, 0]]);
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn unsorted_rows_fail() {
+        // the copy left in execution order, address 5 before address 3
+        let mut circuit = MemoryCircuit::<Fr>::new(vec![[5, 1, 1], [3, 2, 1]]);
+        circuit.sorted = vec![[5, 1, 1, 1], [3, 2, 2, 1]];
+        let prover = MockProver::run(K, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // two accesses to the same address swapped in time
+        let mut circuit = MemoryCircuit::<Fr>::new(vec![[5, 1, 1], [5, 1, 0]]);
+        circuit.sorted.reverse();
+        let prover = MockProver::run(K, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn sorted_rows_must_be_the_accesses() {
+        // a stale read, hidden behind a consistent copy of other accesses
+        let mut circuit = MemoryCircuit::<Fr>::new(vec![[5, 1, 1], [5, 2, 0]]);
+        circuit.sorted = vec![[5, 1, 2, 1], [5, 2, 2, 0]];
+        let prover = MockProver::run(K, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // the accesses the copy stands for are satisfied
+        let circuit = MemoryCircuit::<Fr>::new(vec![[5, 2, 1], [5, 2, 0]]);
+        let prover = MockProver::run(K, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+}
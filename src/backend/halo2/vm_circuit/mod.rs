@@ -1,7 +1,259 @@
 mod binop;
+mod binop16;
 mod blockexit;
 mod call;
-mod unop;
+mod gadgets;
+mod keys;
+mod memory;
+mod pc;
+mod prover;
+mod stack;
+mod unaryop;
+
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    halo2curves::FieldExt,
+    plonk::{Circuit, ConstraintSystem, Error},
+};
 
 use super::table::*;
+use super::utils::{ColumnPool, DynSubCircuit, SubCircuitConfig, BLINDING_ROWS};
 use super::SubCircuit;
+pub use binop::{min_k_for_binop, min_k_for_binop_ops, BinOpCircuit, ExecutionTrace};
+use binop::{BinOpConfig, BinOpConfigArgs};
+use memory::{MemoryCircuit, MemoryConfig, MemoryConfigArgs};
+use pc::{ProgramCounterCircuit, ProgramCounterConfig, ProgramCounterConfigArgs};
+use unaryop::{UnaryOpCircuit, UnaryOpConfig, UnaryOpConfigArgs};
+
+/// Size of the register file of the VM.
+const NUM_REGISTERS: usize = 32;
+
+/// Tables shared by the subcircuits are constructed once here and handed to
+/// every `SubCircuitConfig::new` that needs them.
+#[derive(Clone, Debug)]
+pub struct VmConfig<F: FieldExt> {
+    register_table: RegisterTable,
+    binop: BinOpConfig<F>,
+    unop: UnaryOpConfig<F>,
+    memory: MemoryConfig<F>,
+    pc: ProgramCounterConfig<F>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> VmConfig<F> {
+    fn new(meta: &mut ConstraintSystem<F>) -> Self {
+        let register_table = RegisterTable::construct(meta, NUM_REGISTERS);
+        let binop_table = BinaryOperationTable::construct(meta, 4);
+        let unaryop_table = UnaryOperationTable::construct(meta);
+        let memory_table = MemoryTable::construct(meta);
+        let blockexit_table = BlockExitTable::construct(meta);
+
+        let binop = BinOpConfig::new(
+            meta,
+            BinOpConfigArgs {
+                binop_table,
+                register_table: register_table.clone(),
+                pool: ColumnPool::default(),
+            },
+        );
+        let unop = UnaryOpConfig::new(meta, UnaryOpConfigArgs { unaryop_table });
+        let memory = MemoryConfig::new(meta, MemoryConfigArgs { memory_table });
+        let pc = ProgramCounterConfig::new(meta, ProgramCounterConfigArgs { blockexit_table });
+
+        Self {
+            register_table,
+            binop,
+            unop,
+            memory,
+            pc,
+            _marker: PhantomData::default(),
+        }
+    }
+
+    /// Checks that the tables the subcircuits load fit in a circuit of
+    /// `2^k` rows, blinding rows excluded, so that a too small `k` is
+    /// reported before the keys are generated rather than by the prover.
+    ///
+    /// Tables are in columns of their own, so each must fit on its own.
+    /// The columns themselves do not depend on `k`; see `ConfigStats` for
+    /// their counts.
+    pub fn assert_fits(&self, k: u32) -> Result<(), TableError> {
+        let capacity = (1usize << k).saturating_sub(BLINDING_ROWS);
+        let tables = [
+            ("binop", self.binop.table_rows()),
+            ("unaryop", self.unop.table_rows()),
+            ("register", NUM_REGISTERS),
+        ];
+        for (table, rows) in tables {
+            if rows > capacity {
+                return Err(TableError::TooManyRows {
+                    table,
+                    rows,
+                    capacity,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The whole VM: the register file and one instance of every subcircuit.
+#[derive(Clone, Debug)]
+pub struct VmCircuit<F: FieldExt> {
+    /// Register values, by index.
+    registers: Vec<u64>,
+    binop: BinOpCircuit<F>,
+    unop: UnaryOpCircuit<F>,
+    memory: MemoryCircuit<F>,
+    pc: ProgramCounterCircuit<F>,
+}
+
+impl<F: FieldExt> VmCircuit<F> {
+    pub fn new(
+        registers: Vec<u64>,
+        binop: BinOpCircuit<F>,
+        unop: UnaryOpCircuit<F>,
+        memory: MemoryCircuit<F>,
+        pc: ProgramCounterCircuit<F>,
+    ) -> Self {
+        Self {
+            registers,
+            binop,
+            unop,
+            memory,
+            pc,
+        }
+    }
+
+    /// Public inputs of the subcircuits, currently the binop results.
+    pub fn instance(&self) -> Vec<Vec<F>> {
+        self.binop.instance()
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for VmCircuit<F> {
+    type Config = VmConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            registers: self.registers.clone(),
+            binop: self.binop.without_witnesses(),
+            unop: self.unop.without_witnesses(),
+            memory: self.memory.without_witnesses(),
+            pc: self.pc.without_witnesses(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        VmConfig::new(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let registers = self
+            .registers
+            .iter()
+            .map(|value| Value::known(F::from(*value)))
+            .collect();
+        config.register_table.load(&mut layouter, registers)?;
+        let subcircuits: Vec<Box<dyn DynSubCircuit<F, _>>> = vec![
+            Box::new((&self.binop, &config.binop)),
+            Box::new((&self.unop, &config.unop)),
+            Box::new((&self.memory, &config.memory)),
+            Box::new((&self.pc, &config.pc)),
+        ];
+        for subcircuit in subcircuits {
+            subcircuit.synthesize_sub(&mut layouter)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+
+    use super::*;
+
+    #[test]
+    fn vm_config_fits() {
+        let config = VmCircuit::<Fr>::configure(&mut ConstraintSystem::default());
+        // the 4352 rows of the 4-bit arithmetic table and its padding row
+        assert!(config.assert_fits(13).is_ok());
+        assert!(matches!(
+            config.assert_fits(12),
+            Err(TableError::TooManyRows { table: "binop", .. })
+        ));
+        assert!(matches!(
+            config.assert_fits(4),
+            Err(TableError::TooManyRows { table: "binop", .. })
+        ));
+    }
+
+    #[test]
+    fn vm_circuit_test() {
+        let k = 14;
+        let circuit = VmCircuit::<Fr>::new(
+            vec![3],
+            BinOpCircuit::new(BinOpTag::ADD as u64, 1, 2, 3),
+            UnaryOpCircuit::new(UnaryOpTag::NEG as u64, 0, 15),
+            MemoryCircuit::new(vec![]),
+            ProgramCounterCircuit::new(vec![], vec![[0, 0, 0, 0]], 1),
+        );
+        let prover = MockProver::run(k, &circuit, circuit.instance()).unwrap();
+        prover.assert_satisfied();
+    }
+
+    /// A unop and a memory subcircuit, synthesized from one boxed list.
+    #[derive(Clone, Debug)]
+    struct DynCircuit(UnaryOpCircuit<Fr>, MemoryCircuit<Fr>);
+
+    impl Circuit<Fr> for DynCircuit {
+        type Config = (UnaryOpConfig<Fr>, MemoryConfig<Fr>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self(self.0.without_witnesses(), self.1.without_witnesses())
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let unaryop_table = UnaryOperationTable::construct(meta);
+            let memory_table = MemoryTable::construct(meta);
+            (
+                UnaryOpConfig::new(meta, UnaryOpConfigArgs { unaryop_table }),
+                MemoryConfig::new(meta, MemoryConfigArgs { memory_table }),
+            )
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let subcircuits: Vec<Box<dyn DynSubCircuit<Fr, _>>> = vec![
+                Box::new((&self.0, &config.0)),
+                Box::new((&self.1, &config.1)),
+            ];
+            for subcircuit in subcircuits {
+                subcircuit.synthesize_sub(&mut layouter)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn dyn_sub_circuits() {
+        let circuit = DynCircuit(
+            UnaryOpCircuit::new(UnaryOpTag::NEG as u64, 0, 15),
+            MemoryCircuit::new(vec![[1, 5, 1], [1, 5, 0]]),
+        );
+        let prover = MockProver::run(10, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+}
@@ -0,0 +1,342 @@
+use std::marker::PhantomData;
+
+use super::gadgets::{IsEqualGadget, IsZeroGadget};
+use super::BlockExitTable;
+use super::BlockExitTag;
+use super::LookupTable;
+use super::{SubCircuit, SubCircuitConfig};
+use halo2_proofs::{
+    circuit::{Layouter, Region, SimpleFloorPlanner, Value},
+    halo2curves::FieldExt,
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+/// Advances the program counter by one on sequential steps. On a block exit,
+/// `(exit_tag, cond, pc, target)` must be a row of `blockexit_table`. The
+/// next `pc` is then `target` for `JUMP` and `RET`, while `CONDJUMP` takes a
+/// boolean `cond` and continues at `target` if it is set and at `pc + 1`
+/// otherwise.
+///
+/// The current `pc` is looked up as the `from` of the exit, so a step can
+/// only take the exits of the block it is in.
+///
+/// Which steps are exits is witnessed by `exit_tag` alone, the selector
+/// being the same on every step: a step is sequential if its tag is 0, and
+/// any other tag must be the one of a block exit, as the lookup finds it in
+/// `blockexit_table`. The circuit, and so its keys, do not depend on the
+/// trace.
+#[derive(Clone, Debug)]
+pub struct ProgramCounterConfig<F: FieldExt> {
+    blockexit_table: BlockExitTable,
+    pc: Column<Advice>,
+    exit_tag: Column<Advice>,
+    cond: Column<Advice>,
+    target: Column<Advice>,
+    /// Enabled on every row that has a successor.
+    q_step: Selector,
+    /// Whether `exit_tag` is 0, i.e. the step is sequential.
+    is_sequential: IsZeroGadget<F>,
+    /// Whether `exit_tag` is `CONDJUMP`. The other exits, `JUMP` and `RET`,
+    /// go to `target` unconditionally.
+    is_condjump: IsEqualGadget<F>,
+    _marker: PhantomData<F>,
+}
+
+pub struct ProgramCounterConfigArgs {
+    pub blockexit_table: BlockExitTable,
+}
+
+impl<F: FieldExt> SubCircuitConfig<F> for ProgramCounterConfig<F> {
+    type ConfigArgs = ProgramCounterConfigArgs;
+
+    fn new(
+        meta: &mut ConstraintSystem<F>,
+        ProgramCounterConfigArgs { blockexit_table }: Self::ConfigArgs,
+    ) -> Self {
+        let pc = meta.advice_column();
+        let exit_tag = meta.advice_column();
+        let cond = meta.advice_column();
+        let target = meta.advice_column();
+        let q_step = meta.complex_selector();
+
+        blockexit_table.annotate_columns(meta);
+
+        let is_sequential = IsZeroGadget::configure(
+            meta,
+            |meta| meta.query_selector(q_step),
+            |meta| meta.query_advice(exit_tag, Rotation::cur()),
+        );
+        let is_condjump = IsEqualGadget::configure(
+            meta,
+            |meta| meta.query_selector(q_step),
+            |meta| meta.query_advice(exit_tag, Rotation::cur()),
+            |_| Expression::Constant(F::from(BlockExitTag::CONDJUMP as u64)),
+        );
+
+        meta.create_gate("pc transition", |meta| {
+            let one = Expression::Constant(F::one());
+            let q_step = meta.query_selector(q_step);
+            let is_sequential = is_sequential.expr();
+            let is_jump = one.clone() - is_sequential.clone() - is_condjump.expr();
+            let pc_cur = meta.query_advice(pc, Rotation::cur());
+            let pc_next = meta.query_advice(pc, Rotation::next());
+            let target = meta.query_advice(target, Rotation::cur());
+
+            vec![
+                q_step.clone() * is_sequential * (pc_next.clone() - pc_cur - one),
+                q_step * is_jump * (pc_next - target),
+            ]
+        });
+
+        meta.create_gate("conditional jump", |meta| {
+            let one = Expression::Constant(F::one());
+            let q_condjump = meta.query_selector(q_step) * is_condjump.expr();
+            let pc_cur = meta.query_advice(pc, Rotation::cur());
+            let pc_next = meta.query_advice(pc, Rotation::next());
+            let cond = meta.query_advice(cond, Rotation::cur());
+            let target = meta.query_advice(target, Rotation::cur());
+
+            let taken = cond.clone() * target;
+            let not_taken = (one.clone() - cond.clone()) * (pc_cur + one.clone());
+            vec![
+                q_condjump.clone() * cond.clone() * (one - cond),
+                q_condjump * (pc_next - (taken + not_taken)),
+            ]
+        });
+
+        meta.lookup_any("blockexit_lookup", |meta| {
+            let one = Expression::Constant(F::one());
+            let q_exit = meta.query_selector(q_step) * (one - is_sequential.expr());
+            let step = [exit_tag, cond, pc, target]
+                .map(|column| q_exit.clone() * meta.query_advice(column, Rotation::cur()));
+            step.into_iter()
+                .zip(blockexit_table.table_exprs(meta))
+                .collect()
+        });
+
+        Self {
+            blockexit_table,
+            pc,
+            exit_tag,
+            cond,
+            target,
+            q_step,
+            is_sequential,
+            is_condjump,
+            _marker: PhantomData::default(),
+        }
+    }
+}
+
+impl<F: FieldExt> ProgramCounterConfig<F> {
+    pub fn load_blockexit_table(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        exits: Vec<[u64; 4]>,
+    ) -> Result<(), Error> {
+        self.blockexit_table.load(layouter, exits)?;
+        Ok(())
+    }
+
+    /// Assigns one step at `offset`. With `exit_tag` set the step leaves the
+    /// block as described on `ProgramCounterConfig`, otherwise `cond` and
+    /// `target` are ignored and execution falls through to `pc + 1`. The
+    /// tag is a witness, unknown when synthesizing without witnesses.
+    pub fn assign_step(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        pc: Value<F>,
+        exit_tag: Value<Option<BlockExitTag>>,
+        cond: Value<F>,
+        target: Value<F>,
+    ) -> Result<(), Error> {
+        self.q_step.enable(region, offset)?;
+        region.assign_advice(|| "pc", self.pc, offset, || pc)?;
+        let tag = exit_tag.map(|tag| F::from(tag.map_or(0, |tag| tag as u64)));
+        region.assign_advice(|| "exit tag", self.exit_tag, offset, || tag)?;
+        region.assign_advice(|| "cond", self.cond, offset, || cond)?;
+        region.assign_advice(|| "target", self.target, offset, || target)?;
+        self.is_sequential.assign(region, offset, tag)?;
+        let condjump = Value::known(F::from(BlockExitTag::CONDJUMP as u64));
+        self.is_condjump.assign(region, offset, tag, condjump)?;
+        Ok(())
+    }
+
+    /// Assigns the `pc` reached after the last step.
+    pub fn assign_final_pc(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        pc: Value<F>,
+    ) -> Result<(), Error> {
+        region.assign_advice(|| "pc", self.pc, offset, || pc)?;
+        Ok(())
+    }
+}
+
+/// Witnesses a sequence of `[pc, exit_tag, cond, target]` steps, where an
+/// `exit_tag` of 0 marks a sequential step, followed by the final `pc`.
+#[derive(Clone, Debug)]
+pub struct ProgramCounterCircuit<F: FieldExt> {
+    exits: Vec<[u64; 4]>,
+    steps: Vec<[u64; 4]>,
+    final_pc: u64,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> ProgramCounterCircuit<F> {
+    pub fn new(exits: Vec<[u64; 4]>, steps: Vec<[u64; 4]>, final_pc: u64) -> Self {
+        Self {
+            exits,
+            steps,
+            final_pc,
+            _marker: PhantomData::default(),
+        }
+    }
+}
+
+impl<F: FieldExt> SubCircuit<F> for ProgramCounterCircuit<F> {
+    type Config = ProgramCounterConfig<F>;
+
+    fn synthesize_sub(
+        &self,
+        config: &Self::Config,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        config.load_blockexit_table(layouter, self.exits.clone())?;
+        layouter.assign_region(
+            || "pc",
+            |mut region| {
+                for (offset, [pc, tag, cond, target]) in self.steps.iter().enumerate() {
+                    let exit_tag = match *tag {
+                        0 => None,
+                        tag => Some(BlockExitTag::try_from(tag)?),
+                    };
+                    config.assign_step(
+                        &mut region,
+                        offset,
+                        Value::known(F::from(*pc)),
+                        Value::known(exit_tag),
+                        Value::known(F::from(*cond)),
+                        Value::known(F::from(*target)),
+                    )?;
+                }
+                config.assign_final_pc(
+                    &mut region,
+                    self.steps.len(),
+                    Value::known(F::from(self.final_pc)),
+                )
+            },
+        )
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for ProgramCounterCircuit<F> {
+    type Config = ProgramCounterConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(self.exits.clone(), vec![[0; 4]; self.steps.len()], 0)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let blockexit_table = BlockExitTable::construct(meta);
+        ProgramCounterConfig::new(meta, ProgramCounterConfigArgs { blockexit_table })
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        self.synthesize_sub(&config, &mut layouter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+
+    use super::*;
+
+    const CONDJUMP: u64 = BlockExitTag::CONDJUMP as u64;
+
+    /// Block exit of a `br` at pc 2, to 10 if taken and to 3 otherwise.
+    fn exits() -> Vec<[u64; 4]> {
+        vec![[CONDJUMP, 1, 2, 10], [CONDJUMP, 0, 2, 3]]
+    }
+
+    fn run(steps: Vec<[u64; 4]>, final_pc: u64) -> MockProver<Fr> {
+        run_with_exits(exits(), steps, final_pc)
+    }
+
+    fn run_with_exits(exits: Vec<[u64; 4]>, steps: Vec<[u64; 4]>, final_pc: u64) -> MockProver<Fr> {
+        let circuit = ProgramCounterCircuit::<Fr>::new(exits, steps, final_pc);
+        MockProver::run(5, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn straight_line() {
+        let steps = vec![[0, 0, 0, 0], [1, 0, 0, 0], [2, 0, 0, 0]];
+        run(steps, 3).assert_satisfied();
+        let steps = vec![[0, 0, 0, 0], [2, 0, 0, 0]];
+        assert!(run(steps, 3).verify().is_err());
+    }
+
+    #[test]
+    fn cond_jump_taken() {
+        let steps = vec![[1, 0, 0, 0], [2, CONDJUMP, 1, 10]];
+        run(steps, 10).assert_satisfied();
+    }
+
+    #[test]
+    fn cond_jump_not_taken() {
+        let steps = vec![[1, 0, 0, 0], [2, CONDJUMP, 0, 3]];
+        run(steps, 3).assert_satisfied();
+        let steps = vec![[1, 0, 0, 0], [2, CONDJUMP, 0, 10]];
+        assert!(run(steps, 10).verify().is_err());
+    }
+
+    #[test]
+    fn exit_from_other_block() {
+        // the exits are the ones of the `br` at pc 2, not at pc 5
+        let steps = vec![[4, 0, 0, 0], [5, CONDJUMP, 1, 10]];
+        assert!(run(steps, 10).verify().is_err());
+    }
+
+    #[test]
+    fn keys_do_not_depend_on_exits_taken() {
+        use halo2_proofs::{
+            halo2curves::bn256::Bn256, plonk::keygen_vk, poly::kzg::commitment::ParamsKZG,
+        };
+
+        let params = ParamsKZG::<Bn256>::setup(5, ark_std::test_rng());
+        let pinned_vk = |steps: Vec<[u64; 4]>| {
+            let circuit = ProgramCounterCircuit::<Fr>::new(exits(), steps, 10).without_witnesses();
+            format!("{:?}", keygen_vk(&params, &circuit).unwrap().pinned())
+        };
+        assert_eq!(
+            pinned_vk(vec![[1, 0, 0, 0], [2, CONDJUMP, 1, 10]]),
+            pinned_vk(vec![[1, 0, 0, 0], [2, 0, 0, 0]])
+        );
+    }
+
+    #[test]
+    fn exit_tag_not_a_byte() {
+        // `CONDJUMP` once truncated to a byte
+        let steps = vec![[1, 0, 0, 0], [2, 256 + CONDJUMP, 1, 10]];
+        let circuit = ProgramCounterCircuit::<Fr>::new(exits(), steps, 10);
+        assert!(MockProver::run(5, &circuit, vec![]).is_err());
+    }
+
+    #[test]
+    fn cond_jump_cond_not_boolean() {
+        // Even with a matching block exit, `cond` must be 0 or 1.
+        let mut exits = exits();
+        exits.push([CONDJUMP, 2, 2, 10]);
+        let steps = vec![[1, 0, 0, 0], [2, CONDJUMP, 2, 10]];
+        assert!(run_with_exits(exits, steps, 10).verify().is_err());
+    }
+}
@@ -0,0 +1,213 @@
+use halo2_proofs::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{
+        create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, Error, ProvingKey, VerifyingKey,
+    },
+    poly::kzg::{
+        commitment::{KZGCommitmentScheme, ParamsKZG},
+        multiopen::{ProverSHPLONK, VerifierSHPLONK},
+        strategy::SingleStrategy,
+    },
+    transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+    },
+};
+use rand_core::RngCore;
+
+use super::binop::BinOpCircuit;
+use super::BinOpTag;
+use super::SubCircuit;
+
+/// Setup and keys for `BinOpCircuit`, generated once and reused across proofs.
+///
+/// The keys fix which rows are operations, i.e. enable `q_enable` and are
+/// copied to the instance column, so they are built for `capacity`
+/// operations rather than from a witness. Circuits of fewer operations are
+/// padded to `capacity` before proving, see `BinOpCircuit::pad_ops`.
+///
+/// The KZG parameters are the caller's: they come from a trusted setup,
+/// whose secret nobody knowing it could forge proofs with, so the crate
+/// does not derive them itself.
+pub struct BinOpProver<'params> {
+    params: &'params ParamsKZG<Bn256>,
+    vk: VerifyingKey<G1Affine>,
+    pk: ProvingKey<G1Affine>,
+    capacity: usize,
+}
+
+impl<'params> BinOpProver<'params> {
+    pub fn new(params: &'params ParamsKZG<Bn256>, capacity: usize) -> Result<Self, Error> {
+        let padding = (BinOpTag::ADD as u64, 0, 0, 0);
+        let circuit = BinOpCircuit::<Fr>::from_ops(vec![padding; capacity]).without_witnesses();
+        let vk = keygen_vk(params, &circuit)?;
+        let pk = keygen_pk(params, vk.clone(), &circuit)?;
+        Ok(Self {
+            params,
+            vk,
+            pk,
+            capacity,
+        })
+    }
+
+    pub fn params(&self) -> &'params ParamsKZG<Bn256> {
+        self.params
+    }
+
+    pub fn vk(&self) -> &VerifyingKey<G1Affine> {
+        &self.vk
+    }
+
+    /// Operations a proved circuit may hold.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Proves `circuit`, padded to the capacity of the prover, and returns
+    /// the serialized proof.
+    ///
+    /// `rng` blinds the proof, so it should be a cryptographically secure
+    /// generator such as `rand_core::OsRng`; the crate does not pick one,
+    /// to keep an entropy source out of its dependencies.
+    ///
+    /// Fails with `Error::Synthesis` if `circuit` holds more operations than
+    /// the capacity, or uses registers, immediates or expected results,
+    /// whose selectors the keys do not enable.
+    pub fn prove(&self, circuit: BinOpCircuit<Fr>, rng: impl RngCore) -> Result<Vec<u8>, Error> {
+        if circuit.has_row_options() {
+            return Err(Error::Synthesis);
+        }
+        let circuit = circuit.pad_ops(self.capacity)?;
+        let instance = circuit.instance();
+        let instance = instance.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        create_proof::<
+            KZGCommitmentScheme<Bn256>,
+            ProverSHPLONK<'_, Bn256>,
+            Challenge255<G1Affine>,
+            _,
+            Blake2bWrite<Vec<u8>, G1Affine, Challenge255<G1Affine>>,
+            _,
+        >(
+            self.params,
+            &self.pk,
+            &[circuit],
+            &[&instance[..]],
+            rng,
+            &mut transcript,
+        )?;
+        Ok(transcript.finalize())
+    }
+
+    /// Verifies `proof` against the given instance columns. Rows past the
+    /// given results are 0, as the ones of the padding operations are.
+    pub fn verify(&self, proof: &[u8], public_inputs: &[Vec<Fr>]) -> bool {
+        verify_with_vk(self.params, &self.vk, proof, public_inputs)
+    }
+}
+
+/// Verifies `proof` with a verifying key obtained elsewhere, e.g. from
+/// `keys::read_vk`.
+pub fn verify_with_vk(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proof: &[u8],
+    public_inputs: &[Vec<Fr>],
+) -> bool {
+    let instance = public_inputs.iter().map(Vec::as_slice).collect::<Vec<_>>();
+    let strategy = SingleStrategy::new(params);
+    let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(proof);
+    verify_proof::<
+        KZGCommitmentScheme<Bn256>,
+        VerifierSHPLONK<'_, Bn256>,
+        Challenge255<G1Affine>,
+        Blake2bRead<&[u8], G1Affine, Challenge255<G1Affine>>,
+        SingleStrategy<'_, Bn256>,
+    >(params, vk, strategy, &[&instance[..]], &mut transcript)
+    .is_ok()
+}
+
+/// Proves `circuit` with KZG over bn256 and returns the serialized proof,
+/// blinded with `rng`, see `BinOpProver::prove`. The keys are built for
+/// exactly the operations of `circuit`.
+pub fn prove_binop(
+    circuit: BinOpCircuit<Fr>,
+    params: &ParamsKZG<Bn256>,
+    rng: impl RngCore,
+) -> Result<Vec<u8>, Error> {
+    BinOpProver::new(params, circuit.num_ops())?.prove(circuit, rng)
+}
+
+/// Verifies a proof produced by `prove_binop` against the given instance
+/// columns, which hold one result per operation.
+pub fn verify_binop(params: &ParamsKZG<Bn256>, proof: &[u8], public_inputs: &[Vec<Fr>]) -> bool {
+    let capacity = public_inputs.first().map_or(0, Vec::len);
+    match BinOpProver::new(params, capacity) {
+        Ok(prover) => prover.verify(proof, public_inputs),
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::halo2::table::BinOpTag;
+
+    /// KZG parameters from a public seed, which anyone can forge proofs
+    /// for: only fit for tests.
+    fn setup_params(k: u32) -> ParamsKZG<Bn256> {
+        ParamsKZG::setup(k, ark_std::test_rng())
+    }
+
+    #[test]
+    fn prove_and_verify_add() {
+        let params = setup_params(14);
+        let circuit = BinOpCircuit::new(BinOpTag::ADD as u64, 1, 2, 3);
+        let public_inputs = circuit.instance();
+        let mut proof = prove_binop(circuit, &params, ark_std::test_rng()).unwrap();
+        assert!(verify_binop(&params, &proof, &public_inputs));
+
+        proof[0] ^= 1;
+        assert!(!verify_binop(&params, &proof, &public_inputs));
+    }
+
+    #[test]
+    fn prover_reuses_keys() {
+        let params = setup_params(14);
+        let prover = BinOpProver::new(&params, 2).unwrap();
+        let add = BinOpCircuit::new(BinOpTag::ADD as u64, 1, 2, 3);
+        let mul = BinOpCircuit::from_ops(vec![
+            (BinOpTag::MUL as u64, 3, 4, 12),
+            (BinOpTag::SUB as u64, 9, 4, 5),
+        ]);
+        for circuit in [add, mul] {
+            let public_inputs = circuit.instance();
+            let proof = prover.prove(circuit, ark_std::test_rng()).unwrap();
+            assert!(prover.verify(&proof, &public_inputs));
+        }
+    }
+
+    #[test]
+    fn prover_rejects_wrong_res() {
+        let params = setup_params(14);
+        let prover = BinOpProver::new(&params, 2).unwrap();
+        let circuit = BinOpCircuit::new(BinOpTag::ADD as u64, 1, 2, 4);
+        let public_inputs = circuit.instance();
+        // the lookup may already fail while proving
+        let accepted = prover
+            .prove(circuit, ark_std::test_rng())
+            .is_ok_and(|proof| prover.verify(&proof, &public_inputs));
+        assert!(!accepted);
+
+        let circuit = BinOpCircuit::new(BinOpTag::ADD as u64, 1, 2, 3);
+        let proof = prover.prove(circuit, ark_std::test_rng()).unwrap();
+        assert!(!prover.verify(&proof, &[vec![Fr::from(4)]]));
+    }
+
+    #[test]
+    fn prover_rejects_too_many_ops() {
+        let params = setup_params(14);
+        let prover = BinOpProver::new(&params, 1).unwrap();
+        let circuit = BinOpCircuit::from_ops(vec![(BinOpTag::ADD as u64, 1, 2, 3); 2]);
+        assert!(prover.prove(circuit, ark_std::test_rng()).is_err());
+    }
+}
@@ -0,0 +1,347 @@
+use std::marker::PhantomData;
+
+use super::LookupTable;
+use super::StackTable;
+use super::{SubCircuit, SubCircuitConfig};
+use halo2_proofs::{
+    circuit::{Layouter, Region, SimpleFloorPlanner, Value},
+    halo2curves::FieldExt,
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+/// Checks that every pop in `stack_table` returns the value of the most
+/// recent push that is still on the stack.
+///
+/// Row `i` holds the stack pointer before the `i`-th operation, which starts
+/// at 0, and `top`, the time of the push on top of the stack. A push at time
+/// `t` makes `t` the new top and records the `top` it covers in `prev_top`;
+/// a pop looks up `(top, sp - 1, value, prev_top)` among the pushes and
+/// restores `prev_top` as the new top. The pushes thus form a linked list in
+/// time order, so a pop cannot be matched against a push that was already
+/// popped or covered by a later one.
+///
+/// The time of row `i` is `i + 1`, and the top of the empty stack is 0. The
+/// rows that are no push put `(0, 0, 0, 0)` on the table side of the lookup,
+/// which no pop can match, as its `top` is the time of a push.
+#[derive(Clone, Debug)]
+pub struct StackConfig<F: FieldExt> {
+    stack_table: StackTable,
+    /// Row index of the operation in the region, plus 1.
+    time: Column<Fixed>,
+    /// Time of the push on top of the stack before the operation.
+    top: Column<Advice>,
+    /// On a push, the top it covers. On a pop, the top it restores.
+    prev_top: Column<Advice>,
+    /// Inverse of `sp`, only constrained on pops.
+    sp_inv: Column<Advice>,
+    /// Enabled on the first row, where the stack is empty.
+    q_first: Selector,
+    /// Enabled on every operation row.
+    q_op: Selector,
+    _marker: PhantomData<F>,
+}
+
+pub struct StackConfigArgs {
+    pub stack_table: StackTable,
+}
+
+impl<F: FieldExt> SubCircuitConfig<F> for StackConfig<F> {
+    type ConfigArgs = StackConfigArgs;
+
+    fn new(
+        meta: &mut ConstraintSystem<F>,
+        StackConfigArgs { stack_table }: Self::ConfigArgs,
+    ) -> Self {
+        let time = meta.fixed_column();
+        let top = meta.advice_column();
+        let prev_top = meta.advice_column();
+        let sp_inv = meta.advice_column();
+        let q_first = meta.selector();
+        let q_op = meta.complex_selector();
+
+        stack_table.annotate_columns(meta);
+
+        meta.create_gate("stack first", |meta| {
+            let q_first = meta.query_selector(q_first);
+            let sp = meta.query_advice(stack_table.sp, Rotation::cur());
+            let top = meta.query_advice(top, Rotation::cur());
+            vec![q_first.clone() * sp, q_first * top]
+        });
+
+        meta.create_gate("stack op", |meta| {
+            let one = Expression::Constant(F::one());
+            let two = Expression::Constant(F::from(2));
+            let q_op = meta.query_selector(q_op);
+            let sp_cur = meta.query_advice(stack_table.sp, Rotation::cur());
+            let sp_next = meta.query_advice(stack_table.sp, Rotation::next());
+            let is_push = meta.query_advice(stack_table.is_push, Rotation::cur());
+            let is_pop = one.clone() - is_push.clone();
+            let time = meta.query_fixed(time, Rotation::cur());
+            let top_cur = meta.query_advice(top, Rotation::cur());
+            let top_next = meta.query_advice(top, Rotation::next());
+            let prev_top = meta.query_advice(prev_top, Rotation::cur());
+            let sp_inv = meta.query_advice(sp_inv, Rotation::cur());
+
+            vec![
+                q_op.clone() * is_push.clone() * is_pop.clone(),
+                q_op.clone() * (sp_next - sp_cur.clone() - two * is_push.clone() + one.clone()),
+                q_op.clone()
+                    * (top_next - is_push.clone() * time - is_pop.clone() * prev_top.clone()),
+                q_op.clone() * is_push * (prev_top - top_cur),
+                q_op * is_pop * (sp_cur * sp_inv - one),
+            ]
+        });
+
+        meta.lookup_any("stack pop lookup", |meta| {
+            let one = Expression::Constant(F::one());
+            let q_op = meta.query_selector(q_op);
+            let is_push = meta.query_advice(stack_table.is_push, Rotation::cur());
+            let q_push = q_op.clone() * is_push.clone();
+            let q_pop = q_op * (one.clone() - is_push);
+            let sp = meta.query_advice(stack_table.sp, Rotation::cur());
+            let value = meta.query_advice(stack_table.value, Rotation::cur());
+            let time = meta.query_fixed(time, Rotation::cur());
+            let top = meta.query_advice(top, Rotation::cur());
+            let prev_top = meta.query_advice(prev_top, Rotation::cur());
+
+            vec![
+                (q_pop.clone() * top, q_push.clone() * time),
+                (q_pop.clone() * (sp.clone() - one), q_push.clone() * sp),
+                (q_pop.clone() * value.clone(), q_push.clone() * value),
+                (q_pop * prev_top.clone(), q_push * prev_top),
+            ]
+        });
+
+        Self {
+            stack_table,
+            time,
+            top,
+            prev_top,
+            sp_inv,
+            q_first,
+            q_op,
+            _marker: PhantomData::default(),
+        }
+    }
+}
+
+impl<F: FieldExt> StackConfig<F> {
+    /// Assigns the stack pointer and top before the operation at `offset`,
+    /// or after the last one.
+    pub fn assign_state(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        sp: Value<F>,
+        top: Value<F>,
+    ) -> Result<(), Error> {
+        if offset == 0 {
+            self.q_first.enable(region, offset)?;
+        }
+        let sp_inv = sp.map(|sp| sp.invert().unwrap_or(F::zero()));
+        let time = Value::known(F::from(offset as u64 + 1));
+        region.assign_fixed(|| "time", self.time, offset, || time)?;
+        region.assign_advice(|| "sp", self.stack_table.sp, offset, || sp)?;
+        region.assign_advice(|| "top", self.top, offset, || top)?;
+        region.assign_advice(|| "sp inv", self.sp_inv, offset, || sp_inv)?;
+        Ok(())
+    }
+
+    /// Assigns a push of `value` at `offset`, covering `top`.
+    pub fn assign_push(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        value: Value<F>,
+        top: Value<F>,
+    ) -> Result<(), Error> {
+        self.q_op.enable(region, offset)?;
+        let is_push = Value::known(F::one());
+        region.assign_advice(|| "value", self.stack_table.value, offset, || value)?;
+        region.assign_advice(|| "is_push", self.stack_table.is_push, offset, || is_push)?;
+        region.assign_advice(|| "prev top", self.prev_top, offset, || top)?;
+        Ok(())
+    }
+
+    /// Assigns a pop of `value` at `offset`, restoring `prev_top`, the top
+    /// covered by the push it matches.
+    pub fn assign_pop(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        value: Value<F>,
+        prev_top: Value<F>,
+    ) -> Result<(), Error> {
+        self.q_op.enable(region, offset)?;
+        let is_push = Value::known(F::zero());
+        region.assign_advice(|| "value", self.stack_table.value, offset, || value)?;
+        region.assign_advice(|| "is_push", self.stack_table.is_push, offset, || is_push)?;
+        region.assign_advice(|| "prev top", self.prev_top, offset, || prev_top)?;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum StackEvent {
+    Push(u64),
+    /// A pop claimed to return the given value.
+    Pop(u64),
+}
+
+/// Witnesses a sequence of pushes and pops, given in execution order.
+#[derive(Clone, Debug)]
+pub struct StackCircuit<F: FieldExt> {
+    events: Vec<StackEvent>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> StackCircuit<F> {
+    pub fn new(events: Vec<StackEvent>) -> Self {
+        Self {
+            events,
+            _marker: PhantomData::default(),
+        }
+    }
+}
+
+impl<F: FieldExt> SubCircuit<F> for StackCircuit<F> {
+    type Config = StackConfig<F>;
+
+    fn min_num_rows(&self) -> usize {
+        self.events.len() + 1
+    }
+
+    fn synthesize_sub(
+        &self,
+        config: &Self::Config,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "stack",
+            |mut region| {
+                // `prev_top` of the pushes still on the stack. A pop of the
+                // empty stack keeps `sp` at 0 and is rejected by the circuit
+                // rather than here.
+                let mut pushes: Vec<u64> = vec![];
+                let mut top = 0;
+                for (offset, event) in self.events.iter().enumerate() {
+                    let sp = Value::known(F::from(pushes.len() as u64));
+                    config.assign_state(&mut region, offset, sp, Value::known(F::from(top)))?;
+                    match *event {
+                        StackEvent::Push(value) => {
+                            let value = Value::known(F::from(value));
+                            config.assign_push(
+                                &mut region,
+                                offset,
+                                value,
+                                Value::known(F::from(top)),
+                            )?;
+                            pushes.push(top);
+                            top = offset as u64 + 1;
+                        }
+                        StackEvent::Pop(value) => {
+                            let prev_top = pushes.pop().unwrap_or(0);
+                            let value = Value::known(F::from(value));
+                            let prev = Value::known(F::from(prev_top));
+                            config.assign_pop(&mut region, offset, value, prev)?;
+                            top = prev_top;
+                        }
+                    }
+                }
+                let sp = Value::known(F::from(pushes.len() as u64));
+                config.assign_state(
+                    &mut region,
+                    self.events.len(),
+                    sp,
+                    Value::known(F::from(top)),
+                )
+            },
+        )
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for StackCircuit<F> {
+    type Config = StackConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        let events = self
+            .events
+            .iter()
+            .map(|event| match event {
+                StackEvent::Push(_) => StackEvent::Push(0),
+                StackEvent::Pop(_) => StackEvent::Pop(0),
+            })
+            .collect();
+        Self::new(events)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let stack_table = StackTable::construct(meta);
+        StackConfig::new(meta, StackConfigArgs { stack_table })
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        self.synthesize_sub(&config, &mut layouter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+
+    use super::*;
+
+    fn run(events: Vec<StackEvent>) -> MockProver<Fr> {
+        let circuit = StackCircuit::<Fr>::new(events);
+        MockProver::run(5, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn push_push_pop_pop() {
+        let events = vec![
+            StackEvent::Push(1),
+            StackEvent::Push(2),
+            StackEvent::Pop(2),
+            StackEvent::Pop(1),
+        ];
+        run(events).assert_satisfied();
+    }
+
+    #[test]
+    fn pop_wrong_value_fails() {
+        let events = vec![StackEvent::Push(1), StackEvent::Push(2), StackEvent::Pop(1)];
+        assert!(run(events).verify().is_err());
+    }
+
+    #[test]
+    fn pop_overwritten_value_fails() {
+        // slot 0 held 1 before, but the most recent push there is 2
+        let events = vec![
+            StackEvent::Push(1),
+            StackEvent::Pop(1),
+            StackEvent::Push(2),
+            StackEvent::Pop(1),
+        ];
+        assert!(run(events).verify().is_err());
+    }
+
+    #[test]
+    fn pop_does_not_match_non_push_rows() {
+        // the pushed 5 is on top, the pop cannot match the all-zero rows
+        let events = vec![StackEvent::Push(5), StackEvent::Pop(0)];
+        assert!(run(events).verify().is_err());
+        run(vec![StackEvent::Push(0), StackEvent::Pop(0)]).assert_satisfied();
+    }
+
+    #[test]
+    fn pop_empty_stack_fails() {
+        let events = vec![StackEvent::Push(1), StackEvent::Pop(1), StackEvent::Pop(0)];
+        assert!(run(events).verify().is_err());
+    }
+}
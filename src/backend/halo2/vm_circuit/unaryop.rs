@@ -0,0 +1,230 @@
+use std::marker::PhantomData;
+
+use super::generate_unaryop_table;
+use super::to_field;
+use super::LookupTable;
+use super::PrecomputedFixedTable;
+use super::UnaryOperationTable;
+use super::{SubCircuit, SubCircuitConfig};
+use halo2_proofs::{
+    circuit::{Layouter, Region, SimpleFloorPlanner, Value},
+    halo2curves::FieldExt,
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
+    poly::Rotation,
+};
+
+/// Operand width of the table loaded by `UnaryOpConfig`.
+const BIT_WIDTH: u32 = 4;
+
+#[derive(Clone, Debug)]
+pub struct UnaryOpConfig<F: FieldExt> {
+    unaryop_table: UnaryOperationTable,
+    tag_column: Column<Advice>,
+    operand_column: Column<Advice>,
+    res_column: Column<Advice>,
+    /// Enabled on the operation row. Every lookup input is multiplied by it,
+    /// so the other rows match the unassigned, zero rows of the table.
+    q_enable: Selector,
+    _marker: PhantomData<F>,
+}
+
+pub struct UnaryOpConfigArgs {
+    pub unaryop_table: UnaryOperationTable,
+}
+
+impl<F: FieldExt> SubCircuitConfig<F> for UnaryOpConfig<F> {
+    type ConfigArgs = UnaryOpConfigArgs;
+
+    fn new(
+        meta: &mut ConstraintSystem<F>,
+        UnaryOpConfigArgs { unaryop_table }: Self::ConfigArgs,
+    ) -> Self {
+        let tag_column = meta.advice_column();
+        let operand_column = meta.advice_column();
+        let res_column = meta.advice_column();
+        let q_enable = meta.complex_selector();
+
+        unaryop_table.annotate_columns(meta);
+
+        meta.lookup_any("unary_op_lookup", |meta| {
+            let q_enable = meta.query_selector(q_enable);
+            let tag_value = q_enable.clone() * meta.query_advice(tag_column, Rotation::cur());
+            let operand_value =
+                q_enable.clone() * meta.query_advice(operand_column, Rotation::cur());
+            let res_value = q_enable * meta.query_advice(res_column, Rotation::cur());
+
+            let tag_precompute = meta.query_fixed(unaryop_table.tag, Rotation::cur());
+            let operand_precompute = meta.query_fixed(unaryop_table.operand, Rotation::cur());
+            let res_precompute = meta.query_fixed(unaryop_table.res, Rotation::cur());
+
+            vec![
+                (tag_value, tag_precompute),
+                (operand_value, operand_precompute),
+                (res_value, res_precompute),
+            ]
+        });
+
+        Self {
+            unaryop_table,
+            tag_column,
+            operand_column,
+            res_column,
+            q_enable,
+            _marker: PhantomData::default(),
+        }
+    }
+}
+
+impl<F: FieldExt> UnaryOpConfig<F> {
+    pub fn load_unaryop_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        let precomputed_unaryop = generate_unaryop_table(BIT_WIDTH);
+        self.unaryop_table.load(layouter, precomputed_unaryop)?;
+        Ok(())
+    }
+
+    /// Rows of the table `load_unaryop_table` loads.
+    pub fn table_rows(&self) -> usize {
+        generate_unaryop_table(BIT_WIDTH).len()
+    }
+
+    pub fn load_unaryop_row(
+        &self,
+        region: &mut Region<'_, F>,
+        op: Value<F>,
+        operand: Value<F>,
+        res: Value<F>,
+    ) -> Result<(), Error> {
+        let offset = 0;
+        self.q_enable.enable(region, offset)?;
+        region.assign_advice(|| "tag", self.tag_column, offset, || op)?;
+        region.assign_advice(|| "operand", self.operand_column, offset, || operand)?;
+        region.assign_advice(|| "res", self.res_column, offset, || res)?;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct UnaryOpCircuit<F: FieldExt> {
+    op: Value<F>,
+    operand: Value<F>,
+    res: Value<F>,
+}
+
+impl<F: FieldExt> UnaryOpCircuit<F> {
+    pub fn new(op: u64, operand: u64, res: u64) -> Self {
+        // Any `u64` fits in 64 bits, so the conversions cannot fail.
+        let field = |v: u64| Value::known(to_field::<F>(v.into(), u64::BITS).unwrap());
+        Self {
+            op: field(op),
+            operand: field(operand),
+            res: field(res),
+        }
+    }
+}
+
+impl<F: FieldExt> SubCircuit<F> for UnaryOpCircuit<F> {
+    type Config = UnaryOpConfig<F>;
+
+    fn synthesize_sub(
+        &self,
+        config: &Self::Config,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        config.load_unaryop_table(layouter)?;
+        layouter.assign_region(
+            || "unaryop",
+            |mut region| config.load_unaryop_row(&mut region, self.op, self.operand, self.res),
+        )?;
+        Ok(())
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for UnaryOpCircuit<F> {
+    type Config = UnaryOpConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            op: Value::unknown(),
+            operand: Value::unknown(),
+            res: Value::unknown(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let unaryop_table = UnaryOperationTable::construct(meta);
+        UnaryOpConfig::new(meta, UnaryOpConfigArgs { unaryop_table })
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        self.synthesize_sub(&config, &mut layouter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+
+    use super::*;
+    use crate::backend::halo2::table::UnaryOpTag;
+    use crate::backend::halo2::utils::assert_satisfied;
+
+    #[test]
+    fn unaryop_table_semantics() {
+        let table = generate_unaryop_table(4);
+        assert!(table.contains(&[UnaryOpTag::PLUS as u64, 3, 3]));
+        assert!(table.contains(&[UnaryOpTag::MINUS as u64, 1, 15]));
+        assert!(table.contains(&[UnaryOpTag::NEG as u64, 0, 15]));
+        assert!(table.contains(&[UnaryOpTag::NEG as u64, 3, 12]));
+    }
+
+    #[test]
+    fn unaryop_semantics_at_width_8() {
+        let table = generate_unaryop_table(8);
+        for (op, res) in [
+            (UnaryOpTag::PLUS, 1),
+            (UnaryOpTag::MINUS, 0xff),
+            (UnaryOpTag::NEG, 0xfe),
+            (UnaryOpTag::NOT, 0xfe),
+        ] {
+            assert_eq!(op.eval(1, 8), res);
+            assert!(table.contains(&[op as u64, 1, res]));
+        }
+        // only a single bit wide, the complement is the logical negation
+        assert_eq!(UnaryOpTag::NEG.eval(0, 1), 1);
+        assert_eq!(UnaryOpTag::NEG.eval(1, 1), 0);
+    }
+
+    #[test]
+    fn unaryop_neg_is_not() {
+        assert_eq!(UnaryOpTag::NEG.eval(1, 4), 14);
+        for x in 0..16 {
+            assert_eq!(UnaryOpTag::NEG.eval(x, 4), UnaryOpTag::NOT.eval(x, 4));
+        }
+    }
+
+    #[test]
+    fn unaryop_table_not_is_masked() {
+        let not = UnaryOpTag::NOT as u64;
+        assert!(generate_unaryop_table(8).contains(&[not, 0, 255]));
+        assert!(generate_unaryop_table(4).contains(&[not, 0, 15]));
+        assert!(generate_unaryop_table(4).contains(&[not, 10, 5]));
+    }
+
+    #[test]
+    fn circuit_test() {
+        let circuit = UnaryOpCircuit::<Fr>::new(UnaryOpTag::NEG as u64, 0, 15);
+        assert_satisfied(&circuit, 8);
+    }
+
+    #[test]
+    fn circuit_wrong_res_fails() {
+        let circuit = UnaryOpCircuit::<Fr>::new(UnaryOpTag::NEG as u64, 0, 14);
+        let prover = MockProver::run(8, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
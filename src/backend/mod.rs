@@ -2,6 +2,6 @@
 mod cq;
 
 #[cfg(feature = "halo2")]
-mod halo2;
+pub mod halo2;
 
 use crate::ExecTrace;
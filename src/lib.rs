@@ -1,19 +1,42 @@
-mod backend;
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod backend;
+#[cfg(feature = "std")]
 mod frontend;
+#[cfg(feature = "std")]
 pub mod ir;
+#[cfg(feature = "std")]
 mod irgen;
+/// Tag semantics and table row generation, on `core` and `alloc` alone.
+/// The only module built without the `std` feature; the halo2 backend
+/// re-exports it from `backend::halo2::table`.
+pub mod ops;
+#[cfg(feature = "std")]
 mod opt;
+#[cfg(feature = "std")]
 mod utils;
+#[cfg(feature = "std")]
 mod vm;
+#[cfg(feature = "std")]
 mod write_base;
 
+#[cfg(feature = "std")]
 pub use frontend::utils as frontutils;
+#[cfg(feature = "std")]
 pub use frontend::Parse;
+#[cfg(feature = "std")]
 pub use irgen::Irgen;
+#[cfg(feature = "std")]
 pub use opt::{
     Deadcode, FunctionPass, Gvn, Mem2reg, Optimize, Repeat, SimplifyCfg, SimplifyCfgConstProp,
     SimplifyCfgEmpty, SimplifyCfgMerge, SimplifyCfgReach, O0, O1,
 };
+#[cfg(feature = "std")]
 pub use utils::*;
+#[cfg(feature = "std")]
 pub use vm::{execute, ExecStep, ExecTrace};
+#[cfg(feature = "std")]
 pub use write_base::write;
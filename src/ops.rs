@@ -0,0 +1,493 @@
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter, IntoStaticStr};
+
+/// The low `width` bits set, which the results of `width`-bit ops are
+/// reduced with. Saturates at 64 bits.
+pub const fn mask(width: u32) -> u64 {
+    if width >= u64::BITS {
+        u64::MAX
+    } else {
+        (1 << width) - 1
+    }
+}
+
+/// Largest unsigned `width`-bit value, the last operand the generators
+/// enumerate. The same bits as `mask`, read as a number.
+pub const fn max_value(width: u32) -> u64 {
+    mask(width)
+}
+
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumIter, Display, IntoStaticStr,
+)]
+pub enum BinOpTag {
+    #[strum(serialize = "+")]
+    ADD = 1,
+    #[strum(serialize = "*")]
+    MUL,
+    #[strum(serialize = "-")]
+    SUB,
+    #[strum(serialize = "/")]
+    DIV,
+    #[strum(serialize = "%")]
+    MOD,
+    #[strum(serialize = "<")]
+    LT,
+    #[strum(serialize = ">")]
+    GT,
+    #[strum(serialize = "<=")]
+    LE,
+    #[strum(serialize = ">=")]
+    GE,
+    #[strum(serialize = "<<")]
+    SHL,
+    #[strum(serialize = ">>")]
+    SHR,
+    #[strum(serialize = "&")]
+    AND,
+    #[strum(serialize = "^")]
+    XOR,
+    #[strum(serialize = "|")]
+    OR,
+    #[strum(serialize = "==")]
+    EQ,
+    #[strum(serialize = "!")]
+    NOT,
+    /// Signed variants, reading operands as two's complement words.
+    #[strum(serialize = "slt")]
+    SLT,
+    #[strum(serialize = "sgt")]
+    SGT,
+    #[strum(serialize = "ssub")]
+    SSUB,
+    /// Complements of `AND`, `OR` and `XOR`, masked to the word width.
+    #[strum(serialize = "nand")]
+    NAND,
+    #[strum(serialize = "nor")]
+    NOR,
+    #[strum(serialize = "xnor")]
+    XNOR,
+}
+
+impl BinOpTag {
+    /// Lowercase name of the op in textual IR, e.g. `"add"`. Tags parse
+    /// from it as well as from the symbol they are displayed as.
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            BinOpTag::ADD => "add",
+            BinOpTag::MUL => "mul",
+            BinOpTag::SUB => "sub",
+            BinOpTag::DIV => "div",
+            BinOpTag::MOD => "mod",
+            BinOpTag::LT => "lt",
+            BinOpTag::GT => "gt",
+            BinOpTag::LE => "le",
+            BinOpTag::GE => "ge",
+            BinOpTag::SHL => "shl",
+            BinOpTag::SHR => "shr",
+            BinOpTag::AND => "and",
+            BinOpTag::XOR => "xor",
+            BinOpTag::OR => "or",
+            BinOpTag::EQ => "eq",
+            BinOpTag::NOT => "not",
+            BinOpTag::SLT => "slt",
+            BinOpTag::SGT => "sgt",
+            BinOpTag::SSUB => "ssub",
+            BinOpTag::NAND => "nand",
+            BinOpTag::NOR => "nor",
+            BinOpTag::XNOR => "xnor",
+        }
+    }
+
+    /// Whether `res` of this op can be negative.
+    ///
+    /// Table rows hold such results as the two's complement `u64` of an
+    /// `i64`, see `encode_binop_res` for how they are put in the field.
+    pub fn has_signed_result(&self) -> bool {
+        matches!(self, BinOpTag::SSUB)
+    }
+
+    /// Whether applying this op to `rhs` traps, i.e. divides by zero.
+    ///
+    /// The table still holds a `res` of 0 for such rows, the `is_error`
+    /// column tells it apart from a computed 0.
+    pub fn is_error(&self, rhs: u64) -> bool {
+        matches!(self, BinOpTag::DIV | BinOpTag::MOD) && rhs == 0
+    }
+
+    /// Whether this op overflows a `range`-bit word: `ADD` and `MUL` when
+    /// the exact result does not fit in `range` bits, and `SUB` when it
+    /// borrows, i.e. `lhs < rhs`. The other ops never carry.
+    ///
+    /// `res` is wrapped to the word either way, see `eval`: `255 + 1` at
+    /// range 8 is `0` with a carry.
+    pub fn carry(&self, lhs: u64, rhs: u64, range: u32) -> bool {
+        match self {
+            BinOpTag::ADD | BinOpTag::MUL => self.eval_wide(lhs, rhs, range) >> range != 0,
+            BinOpTag::SUB => lhs < rhs,
+            _ => false,
+        }
+    }
+
+    /// Evaluates this op on `range`-bit operands, returning the result and
+    /// whether the op traps.
+    ///
+    /// Results are `range`-bit words: `ADD`, `MUL` and `SUB` wrap around,
+    /// with `carry` telling whether they did.
+    ///
+    /// `range` must lie in `1..=32` and both operands must fit in it, which
+    /// debug builds assert: the signed ops read bit `range - 1`, and exact
+    /// products of wider operands do not fit in a `u64`.
+    ///
+    /// This is the single definition of the op semantics: the table rows of
+    /// `generate_binop_table` are `[op, lhs, rhs, res]` for every operand
+    /// pair, and witness generators call it directly. `range` is needed by
+    /// the shifts, the complemented bitwise ops and the signed ops only.
+    ///
+    /// The match below has no catch-all arm on purpose: a new tag fails to
+    /// compile until its semantics are defined here, rather than producing
+    /// rows with a `res` of 0.
+    pub fn eval(self, lhs: u64, rhs: u64, range: u32) -> (u64, bool) {
+        debug_check_operands(lhs, rhs, range);
+        let (x, y) = (lhs, rhs);
+        let mask = mask(range);
+        // Only called by the signed ops.
+        let signed = |v: u64| {
+            if v >> (range - 1) == 1 {
+                v as i64 - (1i64 << range)
+            } else {
+                v as i64
+            }
+        };
+        let res = match self {
+            BinOpTag::ADD => x.wrapping_add(y) & mask,
+            BinOpTag::MUL => x.wrapping_mul(y) & mask,
+            BinOpTag::SUB => x.wrapping_sub(y) & mask,
+            BinOpTag::DIV => x.checked_div(y).unwrap_or(0),
+            BinOpTag::MOD => x.checked_rem(y).unwrap_or(0),
+            BinOpTag::LT => (x < y) as u64,
+            BinOpTag::GT => (x > y) as u64,
+            BinOpTag::LE => (x <= y) as u64,
+            BinOpTag::GE => (x >= y) as u64,
+            BinOpTag::SHL if y < range as u64 => (x << y) & mask,
+            BinOpTag::SHR if y < range as u64 => x >> y,
+            BinOpTag::SHL | BinOpTag::SHR => 0,
+            BinOpTag::AND => x & y,
+            BinOpTag::XOR => x ^ y,
+            BinOpTag::OR => x | y,
+            BinOpTag::EQ => (x == y) as u64,
+            BinOpTag::NOT => (x == 0) as u64,
+            BinOpTag::SLT => (signed(x) < signed(y)) as u64,
+            BinOpTag::SGT => (signed(x) > signed(y)) as u64,
+            BinOpTag::SSUB => (signed(x) - signed(y)) as u64,
+            BinOpTag::NAND => !(x & y) & mask,
+            BinOpTag::NOR => !(x | y) & mask,
+            BinOpTag::XNOR => !(x ^ y) & mask,
+        };
+        (res, self.is_error(y))
+    }
+
+    /// Same as `eval`, but `ADD` and `MUL` are not wrapped: the exact sum or
+    /// product, whose bits above `range` are the carry.
+    ///
+    /// Limb-wise circuits compose these exact partial results, see
+    /// `generate_wide_binop_table_for`. The operands are bounded as for
+    /// `eval`, so that the results fit in a `u64`.
+    pub fn eval_wide(self, lhs: u64, rhs: u64, range: u32) -> u64 {
+        debug_check_operands(lhs, rhs, range);
+        let exact = match self {
+            BinOpTag::ADD => lhs.checked_add(rhs),
+            BinOpTag::MUL => lhs.checked_mul(rhs),
+            _ => return self.eval(lhs, rhs, range).0,
+        };
+        exact.expect("operands of at most 32 bits")
+    }
+
+    /// Whether `op(lhs, rhs)` is `op(rhs, lhs)` for all operands, see
+    /// `canonical_binop_table`.
+    pub fn is_commutative(&self) -> bool {
+        matches!(
+            self,
+            BinOpTag::ADD
+                | BinOpTag::MUL
+                | BinOpTag::AND
+                | BinOpTag::OR
+                | BinOpTag::XOR
+                | BinOpTag::EQ
+        )
+    }
+
+    /// Whether this op is an unsigned comparison, whose boolean `res` only
+    /// depends on the order of the operands.
+    ///
+    /// Such ops are left out of `generate_arith_table` and proved through
+    /// the `ComparisonTable` instead. `SLT` and `SGT` stay in the arithmetic
+    /// table, as their order depends on the sign bits.
+    pub fn is_comparison(&self) -> bool {
+        matches!(
+            self,
+            BinOpTag::LT | BinOpTag::GT | BinOpTag::LE | BinOpTag::GE | BinOpTag::EQ
+        )
+    }
+}
+
+/// Asserts, in debug builds, the bounds `BinOpTag::eval` is defined for.
+fn debug_check_operands(lhs: u64, rhs: u64, range: u32) {
+    debug_assert!((1..=32).contains(&range), "range {range} not in 1..=32");
+    debug_assert!(
+        lhs <= mask(range) && rhs <= mask(range),
+        "operands {lhs} and {rhs} do not fit in {range} bits"
+    );
+}
+
+/// Enumerates `[tag, lhs, rhs, res]` for every op over `lhs_range`-bit left
+/// operands and `rhs_range`-bit right operands.
+///
+/// `lhs_range` is the word width the ops are evaluated at, see
+/// `BinOpTag::eval`. A narrower `rhs_range` suits shifts, whose amount only
+/// needs to count up to the word width: `(8, 3)` covers every shift of a
+/// byte in `2^11` rows per op instead of `2^16`.
+///
+/// Results are `lhs_range`-bit words, so ADD, MUL and SUB wrap around
+/// (`15 + 15` at range 4 is `14`) and the table's carry column records the
+/// overflow. A shift amount of `lhs_range` or more yields `0`.
+///
+/// Signed ops read operands as `lhs_range`-bit two's complement words, so at
+/// range 4 the operand `8` is `-8`. A negative `SSUB` result is stored as the
+/// two's complement `u64` of the `i64` and loaded as a negative field element.
+///
+/// The rows are meant to be fed to `BinaryOperationTable::load`:
+///
+/// ```
+/// use lir::backend::halo2::table::{generate_binop_table, BinOpTag};
+///
+/// let rows = generate_binop_table(2, 2);
+/// assert!(rows.contains(&[BinOpTag::ADD as u64, 3, 3, 2]));
+/// // then, while synthesizing: `binop_table.load(&mut layouter, rows)?`
+/// ```
+///
+/// The rows are allocated at once, `estimated_binop_rows` of them; see
+/// `try_generate_binop_table` to bound them.
+///
+/// With the `parallel` feature the operands are enumerated on the rayon
+/// thread pool; the rows come out in the same order either way.
+///
+/// `BinOpConfig` loads the smaller `generate_arith_table` instead and checks
+/// comparisons in-circuit.
+pub fn generate_binop_table(lhs_range: u32, rhs_range: u32) -> Vec<[u64; 4]> {
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        BinOpTag::iter()
+            .flat_map(|op| {
+                (0..=max_value(lhs_range))
+                    .into_par_iter()
+                    .flat_map_iter(move |x| binop_rows(op, x, lhs_range, rhs_range))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        generate_binop_table_serial(lhs_range, rhs_range)
+    }
+}
+
+/// Rows `generate_binop_table(lhs_range, rhs_range)` would return, saturated
+/// at `u128::MAX`, to check the size of a table before generating it.
+pub fn estimated_binop_rows(lhs_range: u32, rhs_range: u32) -> u128 {
+    let num_ops = BinOpTag::iter().count() as u128;
+    1u128
+        .checked_shl(lhs_range + rhs_range)
+        .and_then(|pairs| pairs.checked_mul(num_ops))
+        .unwrap_or(u128::MAX)
+}
+
+#[cfg(any(test, not(feature = "parallel")))]
+pub(crate) fn generate_binop_table_serial(lhs_range: u32, rhs_range: u32) -> Vec<[u64; 4]> {
+    BinOpTag::iter()
+        .flat_map(|op| {
+            (0..=max_value(lhs_range)).flat_map(move |x| binop_rows(op, x, lhs_range, rhs_range))
+        })
+        .collect()
+}
+
+/// Same rows as `generate_binop_table` with both operands `range` bits wide,
+/// restricted to `ops`.
+///
+/// Subcircuits that only look up a few ops can load this smaller table.
+pub fn generate_binop_table_for(ops: &[BinOpTag], range: u32) -> Vec<[u64; 4]> {
+    ops.iter()
+        .flat_map(|op| (0..=max_value(range)).flat_map(move |x| binop_rows(*op, x, range, range)))
+        .collect()
+}
+
+/// Same rows as `generate_binop_table_for`, with the exact results of
+/// `BinOpTag::eval_wide`: `ADD` and `MUL` are not wrapped to the word.
+///
+/// For circuits that split wider operands into `range`-bit limbs and need
+/// the carries out of the limbs.
+pub fn generate_wide_binop_table_for(ops: &[BinOpTag], range: u32) -> Vec<[u64; 4]> {
+    generate_binop_table_for(ops, range)
+        .into_iter()
+        .map(|[tag, lhs, rhs, _]| {
+            let op = ops.iter().find(|op| **op as u64 == tag).unwrap();
+            [tag, lhs, rhs, op.eval_wide(lhs, rhs, range)]
+        })
+        .collect()
+}
+
+/// Same rows as `generate_binop_table`, without the comparison ops.
+///
+/// Comparisons take `2^(2 * range)` rows each here but only need the
+/// `ComparisonTable` and an in-circuit order check, see
+/// `BinOpTag::is_comparison`.
+pub fn generate_arith_table(range: u32) -> Vec<[u64; 4]> {
+    let ops = BinOpTag::iter()
+        .filter(|op| !op.is_comparison())
+        .collect::<Vec<_>>();
+    generate_binop_table_for(&ops, range)
+}
+
+/// Rows of `a` followed by the rows of `b` that are not in `a`, each row
+/// kept once.
+///
+/// Merges the tables of two op allowlists without regenerating them. The
+/// order only depends on the inputs, so the fixed columns a merged table is
+/// loaded into are the same from one run to the next.
+pub fn merge_binop_tables(a: Vec<[u64; 4]>, b: Vec<[u64; 4]>) -> Vec<[u64; 4]> {
+    let mut seen = BTreeSet::new();
+    a.into_iter()
+        .chain(b)
+        .filter(|row| seen.insert(*row))
+        .collect()
+}
+
+/// Rows of `rows`, but the ones of a commutative op with `lhs > rhs`,
+/// which are the rows with `lhs < rhs` mirrored.
+///
+/// Roughly halves the rows of these ops, for circuits that swap the
+/// operands of a commutative op before looking it up.
+pub fn canonical_binop_table(rows: Vec<[u64; 4]>) -> Vec<[u64; 4]> {
+    rows.into_iter()
+        .filter(|[tag, lhs, rhs, _]| {
+            // `TryFrom<u8>` is defined along with the tables, which `ops`
+            // builds without
+            let commutative = matches!(
+                BinOpTag::iter().find(|op| *op as u64 == *tag),
+                Some(op) if op.is_commutative()
+            );
+            !commutative || lhs <= rhs
+        })
+        .collect()
+}
+
+/// Rows of `op` with `lhs = x`, for every `rhs_range`-bit `rhs`.
+fn binop_rows(
+    op: BinOpTag,
+    x: u64,
+    lhs_range: u32,
+    rhs_range: u32,
+) -> impl Iterator<Item = [u64; 4]> {
+    (0..=max_value(rhs_range)).map(move |y| [op as u64, x, y, op.eval(x, y, lhs_range).0])
+}
+
+/// Enumerates `[tag, ge, eq, res]` for every comparison op, where `ge` and
+/// `eq` tell whether `lhs >= rhs` and `lhs == rhs`. `eq` implies `ge`, so each
+/// op has three rows.
+pub fn generate_comparison_table() -> Vec<[u64; 4]> {
+    BinOpTag::iter()
+        .filter(|op| op.is_comparison())
+        .flat_map(|op| {
+            [(0, 0), (1, 0), (1, 1)].map(|(ge, eq)| {
+                // Any operands in that order give the same result.
+                let (x, y) = (ge, 1 - ge + eq);
+                [op as u64, ge, eq, op.eval(x, y, 1).0]
+            })
+        })
+        .collect()
+}
+
+/// Unary ops on `range`-bit words, see `UnaryOpTag::eval`:
+/// - `PLUS` is the identity `x`,
+/// - `MINUS` is the arithmetic negation, the two's complement
+///   `(-x) mod 2^range`, loaded in the field as that non-negative value,
+/// - `NEG` and `NOT` are aliases, both the bitwise complement `!x & mask`.
+///   `NEG` is displayed as C's `!` but is not its logical negation: `NEG 1`
+///   is `14` at range 4, not `0`. The two only agree at range 1.
+#[derive(
+    Clone, Debug, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, EnumIter, Display, IntoStaticStr,
+)]
+pub enum UnaryOpTag {
+    #[strum(serialize = "+")]
+    PLUS = 1,
+    #[strum(serialize = "-")]
+    MINUS,
+    #[strum(serialize = "!")]
+    NEG,
+    /// Bitwise complement, masked to the word width. Same rows as `NEG`.
+    #[strum(serialize = "~")]
+    NOT,
+}
+
+impl UnaryOpTag {
+    /// Lowercase name of the op in textual IR, see `BinOpTag::mnemonic`.
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            UnaryOpTag::PLUS => "plus",
+            UnaryOpTag::MINUS => "minus",
+            UnaryOpTag::NEG => "neg",
+            UnaryOpTag::NOT => "not",
+        }
+    }
+
+    /// Evaluates this op on a `range`-bit operand. The rows of the unary
+    /// table are `[op, x, op.eval(x, range)]`.
+    pub fn eval(self, x: u64, range: u32) -> u64 {
+        let mask = mask(range);
+        match self {
+            UnaryOpTag::PLUS => x,
+            UnaryOpTag::MINUS => x.wrapping_neg() & mask,
+            UnaryOpTag::NEG | UnaryOpTag::NOT => !x & mask,
+        }
+    }
+}
+
+/// Enumerates `[tag, operand, res]` for every unary op over `range`-bit
+/// operands, with the semantics documented on `UnaryOpTag`. Complements are
+/// masked, so `NOT 0` is `15` at range 4 rather than `u64::MAX`.
+pub fn generate_unaryop_table(range: u32) -> Vec<[u64; 3]> {
+    let mut all_cases = Vec::new();
+    for op in UnaryOpTag::iter() {
+        for x in 0..=max_value(range) {
+            all_cases.push([op as u64, x, op.eval(x, range)]);
+        }
+    }
+    all_cases
+}
+
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, EnumIter, Display, IntoStaticStr,
+)]
+pub enum BlockExitTag {
+    #[strum(serialize = "jmp")]
+    JUMP = 1,
+    #[strum(serialize = "br")]
+    CONDJUMP,
+    #[strum(serialize = "ret")]
+    RET,
+}
+
+impl BlockExitTag {
+    /// Lowercase name of the exit in textual IR, see `BinOpTag::mnemonic`.
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            BlockExitTag::JUMP => "jump",
+            BlockExitTag::CONDJUMP => "condjump",
+            BlockExitTag::RET => "ret",
+        }
+    }
+}